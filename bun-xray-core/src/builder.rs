@@ -0,0 +1,318 @@
+//! Programmatic construction of lockfiles, mainly for fixtures.
+//!
+//! ```
+//! use bun_xray_core::builder::LockfileBuilder;
+//!
+//! let mut builder = LockfileBuilder::new("app");
+//! let left_pad = builder.add_package("left-pad", "1.3.0");
+//! builder
+//!     .add_dependency(LockfileBuilder::ROOT, left_pad, "^1.3.0")
+//!     .trust("left-pad");
+//! let bytes = builder.to_bytes().unwrap();
+//! assert!(bun_xray_core::parse_lockfile_from_bytes(&bytes).is_ok());
+//! ```
+
+use crate::hash::{self, name_hash, trusted_hash};
+use crate::model::{
+    BehaviorFlags, CatalogGroup, DependencyEntry, DependencySpec, InstallTree, InstalledPackage, Lockfile,
+    LockfileFormat, OverrideEntry, Package, PackageMeta, PackageOrigin, PatchedEntry,
+    ResolutionKind, TrailerInfo, TreeNode,
+};
+use crate::parser::{CURRENT_FORMAT, PACKAGE_COLUMNS};
+use crate::writer::{write_lockfile, WriteError};
+use std::collections::{HashSet, VecDeque};
+
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Builds a [`Lockfile`] package by package. Package ids are indices in
+/// insertion order; id 0 is the root created by [`LockfileBuilder::new`].
+#[derive(Debug, Clone)]
+pub struct LockfileBuilder {
+    packages: Vec<Package>,
+    trailers: TrailerInfo,
+}
+
+impl LockfileBuilder {
+    /// Id of the root package.
+    pub const ROOT: u32 = 0;
+
+    pub fn new(root_name: &str) -> Self {
+        let root = Package {
+            name: root_name.to_string(),
+            version: String::new(),
+            registry_url: ResolutionKind::Root.registry_url(),
+            integrity_hash: None,
+            resolution: ResolutionKind::Root,
+            dependencies: Vec::new(),
+            meta: PackageMeta::default(),
+            scripts: Default::default(),
+            bin: Default::default(),
+        };
+        LockfileBuilder {
+            packages: vec![root],
+            trailers: TrailerInfo::default(),
+        }
+    }
+
+    /// Adds `name@version` from the public npm registry and returns its id.
+    pub fn add_package(&mut self, name: &str, version: &str) -> u32 {
+        self.add_package_with_resolution(
+            name,
+            ResolutionKind::Npm {
+                version: version.to_string(),
+                registry: DEFAULT_REGISTRY.to_string(),
+            },
+        )
+    }
+
+    /// Adds a package resolved any other way (git, tarball, workspace, ...).
+    pub fn add_package_with_resolution(&mut self, name: &str, resolution: ResolutionKind) -> u32 {
+        let id = self.packages.len() as u32;
+        let origin = match resolution {
+            ResolutionKind::Npm { .. } => PackageOrigin::Npm,
+            ResolutionKind::RemoteTarball { .. } | ResolutionKind::LocalTarball { .. } => {
+                PackageOrigin::Tarball
+            }
+            _ => PackageOrigin::Local,
+        };
+        self.packages.push(Package {
+            name: name.to_string(),
+            version: resolution.version(),
+            registry_url: resolution.registry_url(),
+            integrity_hash: None,
+            resolution,
+            dependencies: Vec::new(),
+            meta: PackageMeta {
+                origin,
+                id,
+                has_install_script: Some(false),
+                ..PackageMeta::default()
+            },
+            scripts: Default::default(),
+            bin: Default::default(),
+        });
+        id
+    }
+
+    /// Direct access for anything without a dedicated setter (bins, scripts,
+    /// os/arch constraints).
+    pub fn package_mut(&mut self, id: u32) -> &mut Package {
+        &mut self.packages[id as usize]
+    }
+
+    /// Points an npm package at another registry, e.g. to model a hijack.
+    pub fn registry(&mut self, id: u32, url: &str) -> &mut Self {
+        let pkg = self.package_mut(id);
+        if let ResolutionKind::Npm { registry, .. } = &mut pkg.resolution {
+            *registry = url.to_string();
+        }
+        pkg.registry_url = url.to_string();
+        self
+    }
+
+    /// Sets the SRI integrity string (`sha512-...`).
+    pub fn integrity(&mut self, id: u32, integrity: &str) -> &mut Self {
+        self.package_mut(id).integrity_hash = Some(integrity.to_string());
+        self
+    }
+
+    /// Adds a production dependency edge `from -> to` with requested range `req`.
+    pub fn add_dependency(&mut self, from: u32, to: u32, req: &str) -> &mut Self {
+        self.add_dependency_with(from, to, req, BehaviorFlags::PROD)
+    }
+
+    pub fn add_dependency_with(
+        &mut self,
+        from: u32,
+        to: u32,
+        req: &str,
+        behavior: BehaviorFlags,
+    ) -> &mut Self {
+        let name = self.packages[to as usize].name.clone();
+        self.package_mut(from).dependencies.push(DependencyEntry {
+            name,
+            req: req.to_string(),
+            spec: DependencySpec::infer(req),
+            behavior,
+            resolved_package_id: Some(to),
+        });
+        self
+    }
+
+    /// Lists `name` in `trustedDependencies`.
+    pub fn trust(&mut self, name: &str) -> &mut Self {
+        self.trailers.trusted_hashes.push(trusted_hash(name));
+        self
+    }
+
+    /// Adds an `overrides` entry forcing `name` to `req`.
+    pub fn override_dependency(&mut self, name: &str, req: &str) -> &mut Self {
+        self.trailers.overrides.push(OverrideEntry {
+            name_hash: name_hash(name),
+            name: None,
+            dependency: DependencyEntry {
+                name: name.to_string(),
+                req: req.to_string(),
+                spec: DependencySpec::infer(req),
+                behavior: BehaviorFlags::empty(),
+                resolved_package_id: None,
+            },
+        });
+        self
+    }
+
+    /// Adds a `patchedDependencies` entry for `name@version`.
+    pub fn patch(&mut self, name_version: &str, patch_path: &str) -> &mut Self {
+        self.trailers.patched.push(PatchedEntry {
+            name_version_hash: name_hash(name_version),
+            name_version: None,
+            path: patch_path.to_string(),
+            patch_hash: None,
+        });
+        self
+    }
+
+    /// Adds `name: req` to the default catalog (`None`) or a named one.
+    pub fn catalog(&mut self, catalog: Option<&str>, name: &str, req: &str) -> &mut Self {
+        let entry = DependencyEntry {
+            name: name.to_string(),
+            req: req.to_string(),
+            spec: DependencySpec::infer(req),
+            behavior: BehaviorFlags::empty(),
+            resolved_package_id: None,
+        };
+        match catalog {
+            None => self.trailers.default_catalog.push(entry),
+            Some(group) => match self.trailers.catalogs.iter_mut().find(|g| g.name == group) {
+                Some(existing) => existing.dependencies.push(entry),
+                None => self.trailers.catalogs.push(CatalogGroup {
+                    name: group.to_string(),
+                    dependencies: vec![entry],
+                }),
+            },
+        }
+        self
+    }
+
+    /// Finishes the model: install tree, meta hash and resolved trailer names.
+    pub fn build(&self) -> Lockfile {
+        let packages = self.packages.clone();
+        let mut trailers = self.trailers.clone();
+        trailers.resolve_names(&packages);
+        let meta_hash = hash::meta_hash(&packages);
+        Lockfile {
+            format_version: CURRENT_FORMAT,
+            source_format: LockfileFormat::Binary,
+            meta_hash,
+            install_tree: hoist(&packages),
+            packages,
+            trailers,
+            computed_meta_hash: Some(meta_hash),
+            name_hash_mismatches: Vec::new(),
+            columns: PACKAGE_COLUMNS.to_vec(),
+            coverage: Default::default(),
+            non_canonical: Vec::new(),
+        }
+    }
+
+    /// Encodes [`LockfileBuilder::build`] as `bun.lockb` bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, WriteError> {
+        write_lockfile(&self.build())
+    }
+}
+
+/// A package waiting to have its dependencies placed.
+struct Placement {
+    package_id: u32,
+    /// `node_modules` nodes visible to the package, nearest last.
+    chain: Vec<usize>,
+    /// Install path and dependency id, until its own `node_modules` exists.
+    own: Option<(String, u32)>,
+}
+
+/// Lays packages out the way a hoisting installer would: everything goes to
+/// the top-level `node_modules` unless a different copy of the same name is
+/// already visible, in which case it is nested under its dependent.
+fn hoist(packages: &[Package]) -> InstallTree {
+    let mut offsets = Vec::with_capacity(packages.len());
+    let mut next = 0u32;
+    for pkg in packages {
+        offsets.push(next);
+        next += pkg.dependencies.len() as u32;
+    }
+
+    let mut nodes = vec![TreeNode {
+        id: 0,
+        parent: None,
+        dependency_id: None,
+        path: String::from("node_modules"),
+        entries: Vec::new(),
+    }];
+    let mut queue = VecDeque::from([Placement {
+        package_id: 0,
+        chain: vec![0],
+        own: None,
+    }]);
+    let mut seen: HashSet<String> = HashSet::new();
+
+    while let Some(Placement {
+        package_id: pkg_id,
+        mut chain,
+        mut own,
+    }) = queue.pop_front()
+    {
+        let pkg = &packages[pkg_id as usize];
+        for (i, dep) in pkg.dependencies.iter().enumerate() {
+            let Some(target) = dep.resolved_package_id else {
+                continue;
+            };
+            if target as usize >= packages.len() {
+                continue;
+            }
+            let visible = chain.iter().rev().find_map(|&n| {
+                nodes[n]
+                    .entries
+                    .iter()
+                    .find(|e| e.name == dep.name)
+                    .map(|e| e.package_id)
+            });
+            let dir = match visible {
+                Some(Some(id)) if id == target => continue,
+                None => 0,
+                // A different copy is visible: nest under this package.
+                Some(_) => match own.take() {
+                    Some((path, dependency_id)) => {
+                        let id = nodes.len();
+                        nodes.push(TreeNode {
+                            id: id as u32,
+                            parent: Some(*chain.last().unwrap() as u32),
+                            dependency_id: Some(dependency_id),
+                            path: format!("{}/node_modules", path),
+                            entries: Vec::new(),
+                        });
+                        chain.push(id);
+                        id
+                    }
+                    None => *chain.last().unwrap(),
+                },
+            };
+            let dependency_id = offsets[pkg_id as usize] + i as u32;
+            let path = format!("{}/{}", nodes[dir].path, dep.name);
+            nodes[dir].entries.push(InstalledPackage {
+                name: dep.name.clone(),
+                path: path.clone(),
+                dependency_id: Some(dependency_id),
+                package_id: Some(target),
+            });
+            if seen.insert(path.clone()) {
+                let visible_chain = chain[..=chain.iter().position(|&n| n == dir).unwrap()].to_vec();
+                queue.push_back(Placement {
+                    package_id: target,
+                    chain: visible_chain,
+                    own: Some((path, dependency_id)),
+                });
+            }
+        }
+    }
+    InstallTree { nodes }
+}
@@ -0,0 +1,170 @@
+//! Checks that a binary lockfile is byte-for-byte what Bun would write.
+//!
+//! Bun zero-initializes every record before filling it, so padding, the tail
+//! of a resolution or bin payload, bytes after an inline string's terminator
+//! and unused integrity digest bytes are always zero. The parser skips over
+//! all of them; a non-zero byte there means the file was produced or edited
+//! by something else.
+
+use crate::model::{NonCanonical, NonCanonicalKind, PackageColumn};
+use crate::parser::{
+    column_record_size, BufferKind, BuffersParseResult, PackageTableHeader,
+    DEPENDENCY_RECORD_SIZE, EXTERN_STRING_RECORD_SIZE,
+};
+use std::ops::Range;
+
+/// Checks the first `len` rows of every column in `columns` and the
+/// dependency and extern string buffers.
+pub(crate) fn check_canonical(
+    bytes: &[u8],
+    table: &PackageTableHeader,
+    columns: &[PackageColumn],
+    len: usize,
+    buffers: &BuffersParseResult,
+) -> Vec<NonCanonical> {
+    let mut out = Checker {
+        bytes,
+        found: Vec::new(),
+    };
+    let mut column_at = table.begin as usize;
+    for &column in columns {
+        let size = column_record_size(column);
+        for idx in 0..len {
+            let at = column_at.saturating_add(idx * size);
+            if at.saturating_add(size) > bytes.len() {
+                break;
+            }
+            out.row(column, idx, at);
+        }
+        column_at = column_at.saturating_add((table.len as usize).saturating_mul(size));
+    }
+
+    for i in 0..buffers.dependencies.len() {
+        let at = buffers.dependencies_at as usize + i * DEPENDENCY_RECORD_SIZE;
+        let path = format!("buffers.{}[{}]", BufferKind::Dependencies.name(), i);
+        out.inline_string(at, None, format!("{}.name", path));
+        out.inline_string(at + 18, None, format!("{}.version", path));
+    }
+    for i in 0..buffers.extern_strings.len() {
+        let at = buffers.extern_strings_at as usize + i * EXTERN_STRING_RECORD_SIZE;
+        out.inline_string(at, None, format!("buffers.{}[{}]", BufferKind::ExternStrings.name(), i));
+    }
+    out.found
+}
+
+struct Checker<'a> {
+    bytes: &'a [u8],
+    found: Vec<NonCanonical>,
+}
+
+impl Checker<'_> {
+    fn row(&mut self, column: PackageColumn, idx: usize, at: usize) {
+        let package = Some(idx as u32);
+        let path = |field: &str| format!("packages[{}].{}", idx, field);
+        let padding = NonCanonicalKind::Padding;
+        match column {
+            PackageColumn::Name => self.inline_string(at, package, path("name")),
+            PackageColumn::Resolution => {
+                self.zeroed(at + 1..at + 8, package, path("resolution.padding"), padding);
+                // The value is padded to 64 bytes after the tag's payload.
+                let payload = match self.bytes[at] {
+                    // npm: url and a full semver version.
+                    2 => 64,
+                    // github and git: five repository strings.
+                    16 | 32 => 40,
+                    // Everything else with a value holds one string.
+                    4 | 8 | 64 | 72 | 80 | 100 => 8,
+                    _ => 0,
+                };
+                self.zeroed(at + 8 + payload..at + 72, package, path("resolution.value"), padding);
+                let strings: &[(usize, &str)] = match self.bytes[at] {
+                    2 => &[(8, "npm.url"), (40, "npm.version.pre"), (56, "npm.version.build")],
+                    16 | 32 => &[
+                        (8, "repository.owner"),
+                        (16, "repository.repo"),
+                        (24, "repository.committish"),
+                        (32, "repository.resolved"),
+                        (40, "repository.package_name"),
+                    ],
+                    4 | 8 | 64 | 72 | 80 | 100 => &[(8, "value")],
+                    _ => &[],
+                };
+                for &(off, field) in strings {
+                    self.inline_string(at + off, package, path(&format!("resolution.{}", field)));
+                }
+            }
+            PackageColumn::Meta => {
+                self.zeroed(at + 1..at + 2, package, path("meta.padding_origin"), padding);
+                self.zeroed(at + 6..at + 8, package, path("meta.padding_os"), padding);
+                self.inline_string(at + 12, package, path("meta.man_dir"));
+                let digest = match self.bytes[at + 20] {
+                    1 => 20,
+                    2 => 32,
+                    3 => 48,
+                    4 => 64,
+                    _ => 0,
+                };
+                self.zeroed(
+                    at + 21 + digest..at + 85,
+                    package,
+                    path("meta.integrity"),
+                    NonCanonicalKind::IntegrityTail,
+                );
+                self.zeroed(at + 86..at + 88, package, path("meta.padding_integrity"), padding);
+            }
+            PackageColumn::Bin => {
+                self.zeroed(at + 1..at + 4, package, path("bin.padding"), padding);
+                let (payload, strings): (usize, &[(usize, &str)]) = match self.bytes[at] {
+                    1 => (8, &[(4, "bin.file")]),
+                    2 => (16, &[(4, "bin.named_file.name"), (12, "bin.named_file.path")]),
+                    3 => (8, &[(4, "bin.dir")]),
+                    // map: an (off, len) slice into extern_strings.
+                    4 => (8, &[]),
+                    _ => (0, &[]),
+                };
+                self.zeroed(at + 4 + payload..at + 20, package, path("bin.value"), padding);
+                for &(off, field) in strings {
+                    self.inline_string(at + off, package, path(field));
+                }
+            }
+            PackageColumn::Scripts => {
+                let hooks = ["preinstall", "install", "postinstall", "preprepare", "prepare", "postprepare"];
+                for (i, hook) in hooks.iter().enumerate() {
+                    self.inline_string(at + i * 8, package, path(&format!("scripts.{}", hook)));
+                }
+                self.zeroed(at + 49..at + 56, package, path("scripts.padding"), padding);
+            }
+            PackageColumn::NameHash | PackageColumn::Dependencies | PackageColumn::Resolutions => {}
+        }
+    }
+
+    /// Records the non-zero bytes in `range`, if any, as one finding.
+    fn zeroed(&mut self, range: Range<usize>, package_id: Option<u32>, field: String, kind: NonCanonicalKind) {
+        let slice = &self.bytes[range.clone()];
+        let (Some(first), Some(last)) = (
+            slice.iter().position(|b| *b != 0),
+            slice.iter().rposition(|b| *b != 0),
+        ) else {
+            return;
+        };
+        self.found.push(NonCanonical {
+            offset: (range.start + first) as u64,
+            len: (last - first + 1) as u64,
+            package_id,
+            field,
+            kind,
+        });
+    }
+
+    /// An inline string ends at its first zero byte; the rest must be zero
+    /// too. External strings have no slack.
+    fn inline_string(&mut self, at: usize, package_id: Option<u32>, field: String) {
+        let raw = &self.bytes[at..at + 8];
+        if raw[7] & 0x80 != 0 {
+            return;
+        }
+        if let Some(end) = raw.iter().position(|b| *b == 0) {
+            self.zeroed(at + end..at + 8, package_id, field, NonCanonicalKind::InlineStringTail);
+        }
+    }
+}
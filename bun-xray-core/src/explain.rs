@@ -0,0 +1,481 @@
+//! Byte-level map of a binary lockfile: which structure every range of the
+//! file encodes, what it decodes to, and what the parser noticed about it.
+
+use crate::model::{BinSpec, LockfileFormat, PackageColumn, ResolutionKind};
+use crate::parser::{
+    column_record_size, column_structure, detect_format, parse_binary_bytes, parse_buffers,
+    parse_trailers, Bin, BufferKind, BuffersParseResult, ExternalSlice,
+    PackageTableHeader, PackageRef, ParseError, Resolution, ResolutionValue, Scripts,
+    SemverString, DEPENDENCY_RECORD_SIZE, EXTERN_STRING_RECORD_SIZE, MAGIC, TREE_RECORD_SIZE,
+};
+use crate::Lockfile;
+use binrw::{BinRead, BinReaderExt};
+use serde::Serialize;
+use std::io::{Cursor, Seek, SeekFrom};
+
+const PACKAGE_TABLE_AT: u64 = 86;
+
+/// One byte range of the file and the structure stored there.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Region {
+    pub start: u64,
+    /// Exclusive.
+    pub end: u64,
+    /// Structure path in the form parse errors use, e.g. `packages[2].resolution`.
+    pub path: String,
+    /// The decoded value, rendered for display.
+    pub value: String,
+    /// The package the bytes belong to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<PackageRef>,
+    /// Parser warnings located in this range and other observations.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl Region {
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Walks a binary lockfile and describes every byte range, in file order.
+/// Containers (a whole buffer) come before the records inside them. The
+/// lockfile's [`Coverage`](crate::model::Coverage) slack shows up as `padding`
+/// when zeroed and `unaccounted` otherwise, and overlapping structures as
+/// `overlap`.
+///
+/// The file is decoded in recovery mode, so damaged lockfiles still map; the
+/// damage shows up as notes on the affected ranges.
+pub fn explain_lockfile(bytes: &[u8]) -> Result<Vec<Region>, ParseError> {
+    if detect_format(bytes) != Some(LockfileFormat::Binary) {
+        return Err(ParseError::InvalidMagic.at(0, "header.magic"));
+    }
+    let (lockfile, warnings) = parse_binary_bytes(bytes, true)?;
+    let header: PackageTableHeader = record(bytes, PACKAGE_TABLE_AT)
+        .ok_or(ParseError::CorruptOffsets(0, PACKAGE_TABLE_AT, bytes.len()))
+        .map_err(|err| err.at(PACKAGE_TABLE_AT, "header.package_table"))?;
+
+    let mut map = Explainer {
+        bytes,
+        lockfile: &lockfile,
+        regions: Vec::new(),
+        strings: Vec::new(),
+        dep_owners: Vec::new(),
+    };
+    map.header(&header);
+    map.columns(&header);
+    let mut scratch = Vec::new();
+    let buffers = parse_buffers(bytes, header.end as usize, true, &mut scratch)?;
+    map.buffers(&buffers, header.end);
+    map.strings(&buffers);
+    map.tail(&buffers);
+    map.slack();
+
+    let mut regions = map.regions;
+    regions.sort_by_key(|r| (r.start, std::cmp::Reverse(r.end)));
+    let notes = warnings
+        .iter()
+        .filter_map(|w| Some((w.offset()?, w.to_string())))
+        .chain(lockfile.non_canonical.iter().map(|n| {
+            (n.offset, format!("{} in {} ({} bytes)", n.kind.label(), n.field, n.len))
+        }));
+    for (offset, note) in notes {
+        let smallest = regions
+            .iter_mut()
+            .filter(|r| r.start <= offset && offset < r.end)
+            .min_by_key(|r| r.len());
+        if let Some(region) = smallest {
+            region.notes.push(note);
+        }
+    }
+    Ok(regions)
+}
+
+/// Reads one little-endian record at `at`, or `None` past the end of `bytes`.
+fn record<T>(bytes: &[u8], at: u64) -> Option<T>
+where
+    for<'a> T: BinRead<Args<'a> = ()>,
+{
+    let mut cursor = Cursor::new(bytes);
+    cursor.seek(SeekFrom::Start(at)).ok()?;
+    cursor.read_le().ok()
+}
+
+/// A pointer into `string_bytes` and the field holding it.
+struct StringRef {
+    off: u32,
+    len: u32,
+    path: String,
+    package: Option<PackageRef>,
+}
+
+struct Explainer<'a> {
+    bytes: &'a [u8],
+    lockfile: &'a Lockfile,
+    regions: Vec<Region>,
+    strings: Vec<StringRef>,
+    /// Each package's dependency slice, to attribute dependency records.
+    dep_owners: Vec<ExternalSlice>,
+}
+
+impl Explainer<'_> {
+    fn push(&mut self, start: u64, len: u64, path: impl Into<String>, value: impl Into<String>) -> &mut Region {
+        self.regions.push(Region {
+            start,
+            end: start + len,
+            path: path.into(),
+            value: value.into(),
+            package: None,
+            notes: Vec::new(),
+        });
+        self.regions.last_mut().unwrap()
+    }
+
+    fn package(&self, idx: usize) -> Option<PackageRef> {
+        self.lockfile.packages.get(idx).map(|pkg| PackageRef {
+            index: idx as u32,
+            name: pkg.name.clone(),
+        })
+    }
+
+    fn string_ref(&mut self, s: &SemverString, path: String, package: Option<PackageRef>) {
+        if let Some((off, len)) = s.external() {
+            self.strings.push(StringRef {
+                off,
+                len,
+                path,
+                package,
+            });
+        }
+    }
+
+    fn header(&mut self, table: &PackageTableHeader) {
+        let lock = self.lockfile;
+        self.push(0, MAGIC.len() as u64, "header.magic", "bun-lockfile-format-v0");
+        self.push(42, 4, "header.format_version", lock.format_version.to_string());
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        let meta_hash = self.push(46, 32, "header.meta_hash", hex(&lock.meta_hash));
+        if let (Some(false), Some(computed)) = (lock.meta_hash_matches(), lock.computed_meta_hash) {
+            meta_hash
+                .notes
+                .push(format!("does not match the packages, which hash to {}", hex(&computed)));
+        }
+        let total_size = record::<u64>(self.bytes, 78).unwrap_or_default();
+        let file_len = self.bytes.len() as u64;
+        let total = self.push(78, 8, "header.total_size", format!("{} bytes", total_size));
+        if total_size != file_len {
+            total.notes.push(format!("the file is {} bytes", file_len));
+        }
+
+        let at = PACKAGE_TABLE_AT;
+        self.push(at, 8, "header.package_table.len", format!("{} packages", table.len));
+        self.push(at + 8, 8, "header.package_table.alignment", table.alignment.to_string());
+        self.push(at + 16, 8, "header.package_table.field_count", format!("{} columns", table.field_count));
+        self.push(at + 24, 8, "header.package_table.begin", format!("{:#x}", table.begin));
+        self.push(at + 32, 8, "header.package_table.end", format!("{:#x}", table.end));
+    }
+
+    fn columns(&mut self, table: &PackageTableHeader) {
+        let lock = self.lockfile;
+        let file_len = self.bytes.len() as u64;
+        let mut at = table.begin;
+        for &column in &lock.columns {
+            let size = column_record_size(column) as u64;
+            let field = &column_structure(column)["packages.".len()..];
+            for (idx, pkg) in lock.packages.iter().enumerate() {
+                let start = at.saturating_add(idx as u64 * size);
+                if start.saturating_add(size) > file_len {
+                    break;
+                }
+                let package = self.package(idx);
+                let path = format!("packages[{}].{}", idx, field);
+                let value = match column {
+                    PackageColumn::Name => {
+                        let name: SemverString = record(self.bytes, start).unwrap();
+                        self.string_ref(&name, path.clone(), package.clone());
+                        pkg.name.clone()
+                    }
+                    PackageColumn::NameHash => format!("{:#018x}", record::<u64>(self.bytes, start).unwrap()),
+                    PackageColumn::Resolution => {
+                        if let Some(res) = record::<Resolution>(self.bytes, start) {
+                            for (field, s) in resolution_strings(&res) {
+                                self.string_ref(s, format!("{}.{}", path, field), package.clone());
+                            }
+                        }
+                        describe_resolution(&pkg.resolution)
+                    }
+                    PackageColumn::Dependencies | PackageColumn::Resolutions => {
+                        let slice: ExternalSlice = record(self.bytes, start).unwrap();
+                        if column == PackageColumn::Dependencies {
+                            self.dep_owners.push(slice);
+                        }
+                        format!("{}[{}..{}]", field, slice.off, slice.off as u64 + slice.len as u64)
+                    }
+                    PackageColumn::Meta => {
+                        let man_dir: SemverString = record(self.bytes, start + 12).unwrap();
+                        self.string_ref(&man_dir, format!("{}.man_dir", path), package.clone());
+                        let mut value = pkg.integrity_hash.clone().unwrap_or_else(|| "no integrity".into());
+                        if pkg.meta.has_install_script == Some(true) {
+                            value.push_str(", has install script");
+                        }
+                        value
+                    }
+                    PackageColumn::Bin => {
+                        let bin: Bin = record(self.bytes, start).unwrap();
+                        let fields: &[(usize, &str)] = match bin.tag {
+                            1 => &[(0, "file")],
+                            2 => &[(0, "named_file.name"), (8, "named_file.path")],
+                            3 => &[(0, "dir")],
+                            _ => &[],
+                        };
+                        for &(off, name) in fields {
+                            let s = SemverString {
+                                bytes: bin.value[off..off + 8].try_into().unwrap(),
+                            };
+                            self.string_ref(&s, format!("{}.{}", path, name), package.clone());
+                        }
+                        describe_bin(&pkg.bin)
+                    }
+                    PackageColumn::Scripts => {
+                        let scripts: Scripts = record(self.bytes, start).unwrap();
+                        for (hook, s) in script_strings(&scripts) {
+                            self.string_ref(s, format!("{}.{}", path, hook), package.clone());
+                        }
+                        let hooks: Vec<String> =
+                            pkg.scripts.iter().map(|(hook, cmd)| format!("{}: {}", hook, cmd)).collect();
+                        if hooks.is_empty() {
+                            "none".into()
+                        } else {
+                            hooks.join("; ")
+                        }
+                    }
+                };
+                self.push(start, size, path, value).package = package;
+            }
+            at = at.saturating_add(table.len.saturating_mul(size));
+        }
+    }
+
+    fn buffers(&mut self, buffers: &BuffersParseResult, pointers_at: u64) {
+        for (i, (kind, begin, end)) in buffers.pointers.iter().enumerate() {
+            let records = end.saturating_sub(*begin) / kind.record_size() as u64;
+            let unit = if *kind == BufferKind::StringBytes { "bytes" } else { "records" };
+            self.push(
+                pointers_at + i as u64 * 16,
+                16,
+                format!("buffers.{}.pointer", kind.name()),
+                format!("{:#x}..{:#x} ({} {})", begin, end, records, unit),
+            );
+        }
+
+        let strings = &buffers.string_bytes;
+        let decode = |s: &SemverString| s.decode(strings).unwrap_or_else(|_| "<undecodable>".into());
+        for (i, dep) in buffers.dependencies.iter().enumerate() {
+            let path = format!("buffers.dependencies[{}]", i);
+            let owner = self
+                .dep_owners
+                .iter()
+                .position(|s| (s.off as usize..s.off as usize + s.len as usize).contains(&i))
+                .and_then(|idx| self.package(idx));
+            self.string_ref(&dep.name, format!("{}.name", path), owner.clone());
+            self.string_ref(&dep.version_literal, format!("{}.version", path), owner.clone());
+            let mut value = format!("{}@{}", decode(&dep.name), decode(&dep.version_literal));
+            if let Some(&target) = buffers.resolutions.get(i) {
+                if (target as usize) < self.lockfile.packages.len() {
+                    value.push_str(&format!(" → packages[{}]", target));
+                }
+            }
+            let start = buffers.dependencies_at + (i * DEPENDENCY_RECORD_SIZE) as u64;
+            self.push(start, DEPENDENCY_RECORD_SIZE as u64, path, value).package = owner;
+        }
+        for (i, s) in buffers.extern_strings.iter().enumerate() {
+            let path = format!("buffers.extern_strings[{}]", i);
+            self.string_ref(&s.value, path.clone(), None);
+            let start = buffers.extern_strings_at + (i * EXTERN_STRING_RECORD_SIZE) as u64;
+            self.push(start, EXTERN_STRING_RECORD_SIZE as u64, path, decode(&s.value));
+        }
+        for (i, tree) in buffers.trees.iter().enumerate() {
+            let value = self
+                .lockfile
+                .install_tree
+                .nodes
+                .iter()
+                .find(|node| node.id == tree.id)
+                .map_or_else(|| format!("tree {}", tree.id), |node| node.path.clone());
+            let start = buffers.trees_at + (i * TREE_RECORD_SIZE) as u64;
+            self.push(start, TREE_RECORD_SIZE as u64, format!("buffers.trees[{}]", i), value);
+        }
+        let whole = [
+            (BufferKind::HoistedDependencies, buffers.hoisted_at, buffers.hoisted.len(), "entries"),
+            (BufferKind::Resolutions, buffers.resolutions_at, buffers.resolutions.len(), "entries"),
+            (BufferKind::StringBytes, buffers.string_bytes_at, strings.len(), "bytes"),
+        ];
+        for (kind, at, count, unit) in whole {
+            if count > 0 {
+                let len = (count * kind.record_size()) as u64;
+                self.push(at, len, format!("buffers.{}", kind.name()), format!("{} {}", count, unit));
+            }
+        }
+    }
+
+    /// One region per distinct string referenced from elsewhere in the file,
+    /// and one per orphaned string.
+    fn strings(&mut self, buffers: &BuffersParseResult) {
+        let strings = &buffers.string_bytes;
+        let mut refs = std::mem::take(&mut self.strings);
+        refs.sort_by_key(|r| (r.off, r.len));
+        let mut last: Option<(u32, u32)> = None;
+        for r in refs {
+            let (start, end) = (r.off as usize, r.off as usize + r.len as usize);
+            if r.len == 0 || end > strings.len() {
+                continue;
+            }
+            let note = format!("referenced by {}", r.path);
+            if last == Some((r.off, r.len)) {
+                self.regions.last_mut().unwrap().notes.push(note);
+                continue;
+            }
+            last = Some((r.off, r.len));
+            let value = String::from_utf8_lossy(&strings[start..end]).into_owned();
+            let region = self.push(
+                buffers.string_bytes_at + r.off as u64,
+                r.len as u64,
+                format!("buffers.string_bytes[{}..{}]", start, end),
+                value,
+            );
+            region.package = r.package;
+            region.notes.push(note);
+        }
+        for orphan in &self.lockfile.coverage.orphan_strings {
+            let start = orphan.offset - buffers.string_bytes_at;
+            let len = orphan.value.len() as u64;
+            self.push(
+                orphan.offset,
+                len,
+                format!("buffers.string_bytes[{}..{}]", start, start + len),
+                orphan.value.clone(),
+            )
+            .notes
+            .push(format!("referenced by nothing (looks like {})", orphan.kind.label()));
+        }
+    }
+
+    /// The sentinel and trailer sections.
+    fn tail(&mut self, buffers: &BuffersParseResult) {
+        let file_len = self.bytes.len() as u64;
+        let sentinel_at = buffers.end_pos as u64;
+        let Some(sentinel) = record::<u64>(self.bytes, sentinel_at) else {
+            return;
+        };
+        self.push(sentinel_at, 8, "sentinel", sentinel.to_string());
+
+        let total_size = record::<u64>(self.bytes, 78).unwrap_or_default().min(file_len);
+        let mut cursor = Cursor::new(self.bytes);
+        cursor.set_position(sentinel_at + 8);
+        let mut spans = Vec::new();
+        // Failures are already notes from the recovering parse; the spans read
+        // before one are still worth showing.
+        let _ = parse_trailers(
+            &mut cursor,
+            total_size,
+            &buffers.string_bytes,
+            &mut Vec::new(),
+            &mut spans,
+        );
+        for span in spans {
+            let end = span.end.min(file_len);
+            self.push(span.start, end - span.start, format!("trailers.{}", span.section), span.tag);
+        }
+    }
+
+    /// The parser's slack ranges, and where its spans collide.
+    fn slack(&mut self) {
+        let coverage = &self.lockfile.coverage;
+        for slack in &coverage.slack {
+            if slack.zeroed {
+                self.push(slack.offset, slack.len, "padding", format!("{} zero bytes", slack.len));
+            } else {
+                self.push(slack.offset, slack.len, "unaccounted", format!("{} bytes", slack.len))
+                    .notes
+                    .push(format!(
+                        "no structure accounts for these bytes (entropy {:.2} bits/byte)",
+                        slack.entropy
+                    ));
+            }
+        }
+        for (first, second) in &coverage.overlaps {
+            let start = second.start.max(first.start);
+            let end = second.end.min(first.end);
+            self.push(start, end - start, "overlap", format!("{} and {}", first.structure, second.structure))
+                .notes
+                .push("claimed by two structures".into());
+        }
+    }
+}
+
+fn describe_resolution(resolution: &ResolutionKind) -> String {
+    match resolution {
+        ResolutionKind::Npm { version, registry } => format!("npm {} from {}", version, registry),
+        ResolutionKind::Root => "root".into(),
+        ResolutionKind::Unknown(_) => "uninitialized".into(),
+        other => other.bun_display(),
+    }
+}
+
+fn describe_bin(bin: &BinSpec) -> String {
+    match bin {
+        BinSpec::None => "none".into(),
+        BinSpec::File { path } | BinSpec::Dir { path } => path.clone(),
+        BinSpec::NamedFile { name, path } => format!("{} → {}", name, path),
+        BinSpec::Map { entries } => format!("{} executables", entries.len()),
+        BinSpec::Unknown(tag) => format!("unknown tag {}", tag),
+    }
+}
+
+/// String fields of a resolution record, by path relative to the record.
+fn resolution_strings(res: &Resolution) -> Vec<(&'static str, &SemverString)> {
+    match &res.value {
+        ResolutionValue::Uninitialized | ResolutionValue::Root => vec![],
+        ResolutionValue::Npm(v) => vec![
+            ("npm.url", &v.url),
+            ("npm.version.pre", &v.version.tag.pre.value),
+            ("npm.version.build", &v.version.tag.build.value),
+        ],
+        ResolutionValue::Folder(s) => vec![("folder", s)],
+        ResolutionValue::LocalTarball(s) => vec![("local_tarball", s)],
+        ResolutionValue::RemoteTarball(s) => vec![("remote_tarball", s)],
+        ResolutionValue::Symlink(s) => vec![("symlink", s)],
+        ResolutionValue::Workspace(s) => vec![("workspace", s)],
+        ResolutionValue::SingleFileModule(s) => vec![("single_file_module", s)],
+        ResolutionValue::Git(repo) => vec![
+            ("git.owner", &repo.owner),
+            ("git.repo", &repo.repo),
+            ("git.committish", &repo.committish),
+            ("git.resolved", &repo.resolved),
+            ("git.package_name", &repo.package_name),
+        ],
+        ResolutionValue::Github(repo) => vec![
+            ("github.owner", &repo.owner),
+            ("github.repo", &repo.repo),
+            ("github.committish", &repo.committish),
+            ("github.resolved", &repo.resolved),
+            ("github.package_name", &repo.package_name),
+        ],
+    }
+}
+
+fn script_strings(scripts: &Scripts) -> [(&'static str, &SemverString); 6] {
+    [
+        ("preinstall", &scripts.preinstall),
+        ("install", &scripts.install),
+        ("postinstall", &scripts.postinstall),
+        ("preprepare", &scripts.preprepare),
+        ("prepare", &scripts.prepare),
+        ("postprepare", &scripts.postprepare),
+    ]
+}
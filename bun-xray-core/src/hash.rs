@@ -0,0 +1,139 @@
+//! Bun's string hashing.
+//!
+//! Bun keys package names, trusted dependencies, overrides and patches by
+//! `String.Builder.stringHash`, which is the legacy (pre Zig 0.11) Wyhash with
+//! a zero seed. Bun vendors it as `Wyhash11`; this is a straight port.
+//!
+//! The lockfile header's meta hash is a SHA-512/256 digest over the sorted
+//! `name@version` list, see [`meta_hash`].
+
+use crate::model::{Package, ResolutionKind};
+use crate::semver::Version;
+use sha2::{Digest, Sha512_256};
+use std::cmp::Ordering;
+
+const PRIMES: [u64; 5] = [
+    0xa076_1d64_78bd_642f,
+    0xe703_7ed1_a0b4_28db,
+    0x8ebc_6af0_9c88_c6e3,
+    0x5899_65cc_7537_4cc3,
+    0x1d8e_4e27_c47d_124f,
+];
+
+/// Hash of a package name as stored in `name_hash` columns and trailers.
+pub fn name_hash(name: &str) -> u64 {
+    wyhash11(0, name.as_bytes())
+}
+
+/// Hash stored in the `trustedDependencies` trailer (truncated to 32 bits).
+pub fn trusted_hash(name: &str) -> u32 {
+    name_hash(name) as u32
+}
+
+/// Recomputes the header meta hash the way Bun's `generateMetaHash` does:
+/// every package except the root, sorted by name and then resolution, printed
+/// as `name@resolution` lines between fixed markers. Lockfiles with at most
+/// one package hash to all zeroes.
+pub fn meta_hash(packages: &[Package]) -> [u8; 32] {
+    if packages.len() <= 1 {
+        return [0u8; 32];
+    }
+    let mut sorted: Vec<&Package> = packages[1..].iter().collect();
+    sorted.sort_by(|a, b| alphabetize(a, b));
+
+    let mut hasher = Sha512_256::new();
+    hasher.update(b"\n-- BEGIN SHA512/256(`${alphabetize(name)}@${order(version)}`) --\n");
+    for pkg in sorted {
+        hasher.update(format!("{}@{}\n", pkg.name, pkg.resolution.bun_display()).as_bytes());
+    }
+    hasher.update(b"-- END HASH--\n");
+    hasher.finalize().into()
+}
+
+/// `Package.Alphabetizer`: name bytes first, then `Resolution.order`.
+fn alphabetize(a: &Package, b: &Package) -> Ordering {
+    a.name
+        .as_bytes()
+        .cmp(b.name.as_bytes())
+        .then_with(|| a.resolution.tag_order().cmp(&b.resolution.tag_order()))
+        .then_with(|| match (&a.resolution, &b.resolution) {
+            (ResolutionKind::Npm { version: va, .. }, ResolutionKind::Npm { version: vb, .. }) => {
+                match (Version::parse(va), Version::parse(vb)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    _ => va.cmp(vb),
+                }
+            }
+            (ra, rb) => ra.bun_display().cmp(&rb.bun_display()),
+        })
+}
+
+/// Legacy Wyhash as used by Bun for lockfile hashes.
+pub fn wyhash11(seed: u64, input: &[u8]) -> u64 {
+    let aligned_len = input.len() - (input.len() % 32);
+    let mut seed = seed;
+    for block in input[..aligned_len].chunks_exact(32) {
+        seed = mix0(read_u64(&block[0..]), read_u64(&block[8..]), seed)
+            ^ mix1(read_u64(&block[16..]), read_u64(&block[24..]), seed);
+    }
+
+    let rem = &input[aligned_len..];
+    let seed = match rem.len() {
+        0 => seed,
+        1..=7 => mix0(read_partial(rem), PRIMES[4], seed),
+        8 => mix0(read_swapped(rem), PRIMES[4], seed),
+        9..=15 => mix0(read_swapped(rem), read_partial(&rem[8..]), seed),
+        16 => mix0(read_swapped(rem), read_swapped(&rem[8..]), seed),
+        n => {
+            let head = mix0(read_swapped(rem), read_swapped(&rem[8..]), seed);
+            let tail = match n {
+                17..=23 => mix1(read_partial(&rem[16..]), PRIMES[4], seed),
+                24 => mix1(read_swapped(&rem[16..]), PRIMES[4], seed),
+                _ => mix1(read_swapped(&rem[16..]), read_partial(&rem[24..]), seed),
+            };
+            head ^ tail
+        }
+    };
+
+    mum(seed ^ input.len() as u64, PRIMES[4])
+}
+
+fn mum(a: u64, b: u64) -> u64 {
+    let r = (a as u128) * (b as u128);
+    ((r >> 64) ^ r) as u64
+}
+
+fn mix0(a: u64, b: u64, seed: u64) -> u64 {
+    mum(a ^ seed ^ PRIMES[0], b ^ seed ^ PRIMES[1])
+}
+
+fn mix1(a: u64, b: u64, seed: u64) -> u64 {
+    mum(a ^ seed ^ PRIMES[2], b ^ seed ^ PRIMES[3])
+}
+
+fn read_le(bytes: &[u8], n: usize) -> u64 {
+    bytes[..n]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, b| (acc << 8) | u64::from(*b))
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    read_le(bytes, 8)
+}
+
+fn read_swapped(bytes: &[u8]) -> u64 {
+    (read_le(bytes, 4) << 32) | read_le(&bytes[4..], 4)
+}
+
+/// Reads a 1..=7 byte tail the way Wyhash's `final` switch does.
+fn read_partial(bytes: &[u8]) -> u64 {
+    match bytes.len() {
+        1 => read_le(bytes, 1),
+        2 => read_le(bytes, 2),
+        3 => (read_le(bytes, 2) << 8) | read_le(&bytes[2..], 1),
+        4 => read_le(bytes, 4),
+        5 => (read_le(bytes, 4) << 8) | read_le(&bytes[4..], 1),
+        6 => (read_le(bytes, 4) << 16) | read_le(&bytes[4..], 2),
+        _ => (read_le(bytes, 4) << 24) | (read_le(&bytes[4..], 2) << 8) | read_le(&bytes[6..], 1),
+    }
+}
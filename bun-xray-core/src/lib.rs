@@ -1,9 +1,25 @@
+pub mod builder;
+mod canonical;
+pub mod explain;
+pub mod hash;
 pub mod model;
 pub mod package_json;
 pub mod parser;
 pub mod security;
+pub mod semver;
+pub mod text_lockfile;
+pub mod writer;
 
-pub use model::{Lockfile, Package};
+pub use builder::LockfileBuilder;
+pub use explain::{explain_lockfile, Region};
+pub use model::{Lockfile, LockfileFormat, Package};
 pub use package_json::{load_package_json, PackageJson};
-pub use parser::{parse_lockfile, parse_lockfile_with_warnings, ParseError};
+pub use parser::{
+    detect_format, parse_any_lockfile, parse_any_lockfile_with_warnings, parse_lockfile,
+    parse_lockfile_from_bytes, parse_lockfile_from_bytes_with_options, parse_lockfile_from_reader,
+    parse_lockfile_with_options, parse_lockfile_with_warnings, PackageRef, ParseError,
+    ParseOptions, ParseWarning,
+};
 pub use security::{ScanResult, SecurityScanner};
+pub use text_lockfile::{parse_text_lockfile, parse_text_lockfile_with_warnings};
+pub use writer::{write_lockfile, write_lockfile_to_path, WriteError};
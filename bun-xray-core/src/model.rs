@@ -1,12 +1,29 @@
+use crate::hash::{name_hash, trusted_hash};
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ResolutionKind {
     Root,
     Npm { version: String, registry: String },
-    Git { repo: String, commit: String },
-    Github { owner: String, repo: String, reference: String },
+    /// `resolved` is the commit Bun pinned (for github, `owner-repo-<sha>`);
+    /// empty until resolved.
+    Git {
+        #[serde(default)]
+        owner: String,
+        repo: String,
+        commit: String,
+        #[serde(default)]
+        resolved: String,
+    },
+    Github {
+        owner: String,
+        repo: String,
+        reference: String,
+        #[serde(default)]
+        resolved: String,
+    },
     Folder { path: String },
     Symlink { path: String },
     Workspace { name: String },
@@ -16,6 +33,125 @@ pub enum ResolutionKind {
     Unknown(String),
 }
 
+impl ResolutionKind {
+    /// Version reported for the package; only npm resolutions carry one.
+    pub(crate) fn version(&self) -> String {
+        match self {
+            ResolutionKind::Npm { version, .. } => version.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// The resolution as Bun's `Resolution.Formatter` prints it; this is the
+    /// `{version}` half of each `name@version` line in the meta hash.
+    pub(crate) fn bun_display(&self) -> String {
+        match self {
+            ResolutionKind::Npm { version, .. } => version.clone(),
+            ResolutionKind::LocalTarball { path } => path.clone(),
+            ResolutionKind::Folder { path } => path.clone(),
+            ResolutionKind::RemoteTarball { url } => url.clone(),
+            ResolutionKind::Git {
+                owner,
+                repo,
+                commit,
+                resolved,
+            } => git_display("git+", owner, repo, commit, resolved),
+            ResolutionKind::Github {
+                owner,
+                repo,
+                reference,
+                resolved,
+            } => git_display("github:", owner, repo, reference, resolved),
+            ResolutionKind::Workspace { name } => format!("workspace:{}", name),
+            ResolutionKind::Symlink { path } => format!("link:{}", path),
+            ResolutionKind::SingleFileModule { url } => format!("module:{}", url),
+            ResolutionKind::Root | ResolutionKind::Unknown(_) => String::new(),
+        }
+    }
+
+    /// Position of the resolution tag in Bun's enum, used to order packages
+    /// that share a name.
+    pub(crate) fn tag_order(&self) -> u8 {
+        match self {
+            ResolutionKind::Unknown(_) => 0,
+            ResolutionKind::Root => 1,
+            ResolutionKind::Npm { .. } => 2,
+            ResolutionKind::Folder { .. } => 4,
+            ResolutionKind::LocalTarball { .. } => 8,
+            ResolutionKind::Github { .. } => 16,
+            ResolutionKind::Git { .. } => 32,
+            ResolutionKind::Symlink { .. } => 64,
+            ResolutionKind::Workspace { .. } => 72,
+            ResolutionKind::RemoteTarball { .. } => 80,
+            ResolutionKind::SingleFileModule { .. } => 100,
+        }
+    }
+
+    /// Where the package is fetched from, as surfaced in `Package::registry_url`.
+    pub(crate) fn registry_url(&self) -> String {
+        match self {
+            ResolutionKind::Npm { registry, .. } => registry.clone(),
+            ResolutionKind::RemoteTarball { url } => url.clone(),
+            ResolutionKind::LocalTarball { path } => path.clone(),
+            ResolutionKind::Git { repo, .. } => repo.clone(),
+            ResolutionKind::Github { owner, repo, .. } => format!("{}/{}", owner, repo),
+            ResolutionKind::Folder { path } => path.clone(),
+            ResolutionKind::Symlink { path } => path.clone(),
+            ResolutionKind::Workspace { name } => name.clone(),
+            ResolutionKind::SingleFileModule { url } => url.clone(),
+            ResolutionKind::Root => String::from("root"),
+            ResolutionKind::Unknown(s) => s.clone(),
+        }
+    }
+}
+
+/// `Repository.Formatter`: label, `owner/` (or `ssh://` for an SCP-like
+/// repo without one), repo, then `#` and the resolved commit (what follows
+/// its last `-`) or, before resolution, the committish.
+fn git_display(label: &str, owner: &str, repo: &str, committish: &str, resolved: &str) -> String {
+    let mut out = String::from(label);
+    if !owner.is_empty() {
+        out.push_str(owner);
+        out.push('/');
+    } else if is_scp_like_path(repo) {
+        out.push_str("ssh://");
+    }
+    out.push_str(repo);
+    if !resolved.is_empty() {
+        out.push('#');
+        out.push_str(resolved.rsplit('-').next().unwrap_or(resolved));
+    } else if !committish.is_empty() {
+        out.push('#');
+        out.push_str(committish);
+    }
+    out
+}
+
+/// Bun's `Dependency.isSCPLikePath`: `host:path` or `user@host:path`, but
+/// not a `scheme://` URL.
+fn is_scp_like_path(repo: &str) -> bool {
+    if repo.len() < 3 {
+        return false;
+    }
+    let mut at_index = None;
+    for (i, c) in repo.bytes().enumerate() {
+        match c {
+            b'@' => {
+                at_index.get_or_insert(i);
+            }
+            b':' => {
+                if repo[i..].starts_with("://") {
+                    return false;
+                }
+                return i > at_index.map_or(0, |at| at + 1);
+            }
+            b'/' => return at_index.is_some_and(|at| i > at + 1),
+            _ => {}
+        }
+    }
+    false
+}
+
 bitflags! {
     #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
     pub struct BehaviorFlags: u8 {
@@ -28,14 +164,440 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// npm `os` constraint, as Bun's `OperatingSystem` bitset.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+    pub struct OsFlags: u16 {
+        const AIX     = 1 << 1;
+        const DARWIN  = 1 << 2;
+        const FREEBSD = 1 << 3;
+        const LINUX   = 1 << 4;
+        const OPENBSD = 1 << 5;
+        const SUNOS   = 1 << 6;
+        const WIN32   = 1 << 7;
+        const ANDROID = 1 << 8;
+    }
+}
+
+bitflags! {
+    /// npm `cpu` constraint, as Bun's `Architecture` bitset.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+    pub struct ArchFlags: u16 {
+        const ARM     = 1 << 1;
+        const ARM64   = 1 << 2;
+        const IA32    = 1 << 3;
+        const MIPS    = 1 << 4;
+        const MIPSEL  = 1 << 5;
+        const PPC     = 1 << 6;
+        const PPC64   = 1 << 7;
+        const S390    = 1 << 8;
+        const S390X   = 1 << 9;
+        const X32     = 1 << 10;
+        const X64     = 1 << 11;
+    }
+}
+
+impl OsFlags {
+    /// Applies an npm `os` list (`["darwin", "!win32"]`); an empty list means all.
+    pub fn from_npm_list<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        apply_npm_list(names, |name| Self::from_name(&name.to_ascii_uppercase()))
+    }
+
+    /// `any`, or the npm names joined by `,` (e.g. `darwin,linux`).
+    pub fn label(&self) -> String {
+        flags_label(*self)
+    }
+}
+
+impl ArchFlags {
+    /// Applies an npm `cpu` list (`["x64", "!arm"]`); an empty list means all.
+    pub fn from_npm_list<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        apply_npm_list(names, |name| Self::from_name(&name.to_ascii_uppercase()))
+    }
+
+    /// `any`, or the npm names joined by `,` (e.g. `arm64,x64`).
+    pub fn label(&self) -> String {
+        flags_label(*self)
+    }
+}
+
+fn flags_label<F: bitflags::Flags>(flags: F) -> String {
+    if flags.is_all() {
+        return String::from("any");
+    }
+    if flags.is_empty() {
+        return String::from("none");
+    }
+    flags
+        .iter_names()
+        .map(|(name, _)| name.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn apply_npm_list<'a, F>(
+    names: impl IntoIterator<Item = &'a str>,
+    lookup: impl Fn(&str) -> Option<F>,
+) -> F
+where
+    F: bitflags::Flags + Copy,
+{
+    let mut allowed = F::empty();
+    let mut denied = F::empty();
+    for name in names {
+        match name.strip_prefix('!') {
+            Some(negated) => denied.insert(lookup(negated).unwrap_or(F::empty())),
+            None => allowed.insert(lookup(name).unwrap_or(F::empty())),
+        }
+    }
+    if allowed.is_empty() {
+        allowed = F::all();
+    }
+    allowed.difference(denied)
+}
+
+/// Where Bun fetched a package from (`Meta.origin`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageOrigin {
+    #[default]
+    Local,
+    Npm,
+    Tarball,
+    Unknown(u8),
+}
+
+impl From<u8> for PackageOrigin {
+    fn from(raw: u8) -> Self {
+        match raw {
+            0 => PackageOrigin::Local,
+            1 => PackageOrigin::Npm,
+            2 => PackageOrigin::Tarball,
+            other => PackageOrigin::Unknown(other),
+        }
+    }
+}
+
+impl From<PackageOrigin> for u8 {
+    fn from(origin: PackageOrigin) -> Self {
+        match origin {
+            PackageOrigin::Local => 0,
+            PackageOrigin::Npm => 1,
+            PackageOrigin::Tarball => 2,
+            PackageOrigin::Unknown(raw) => raw,
+        }
+    }
+}
+
+/// Per-package `Meta` column.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct PackageMeta {
+    pub origin: PackageOrigin,
+    pub os: OsFlags,
+    pub arch: ArchFlags,
+    /// Package id recorded in the meta column.
+    pub id: u32,
+    /// `None` when the lockfile predates the flag (Bun's `old` state).
+    pub has_install_script: Option<bool>,
+}
+
+impl Default for PackageMeta {
+    fn default() -> Self {
+        PackageMeta {
+            origin: PackageOrigin::default(),
+            os: OsFlags::all(),
+            arch: ArchFlags::all(),
+            id: 0,
+            has_install_script: None,
+        }
+    }
+}
+
+/// Executables a package links into `node_modules/.bin` (Bun's `Bin` column).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum BinSpec {
+    #[default]
+    None,
+    /// `"bin": "cli.js"`; the executable is named after the package.
+    File { path: String },
+    /// A single `"bin": { name: path }` entry.
+    NamedFile { name: String, path: String },
+    /// `"directories": { "bin": dir }`; every file in `dir` is linked.
+    Dir { path: String },
+    /// `"bin": { name: path, ... }` as `(name, path)` pairs.
+    Map { entries: Vec<(String, String)> },
+    Unknown(u8),
+}
+
+impl BinSpec {
+    /// Executable names this package exposes. Directory bins cannot be listed
+    /// from the lockfile alone and yield nothing.
+    pub fn executables(&self, package_name: &str) -> Vec<String> {
+        match self {
+            BinSpec::File { .. } => {
+                let unscoped = package_name.rsplit('/').next().unwrap_or(package_name);
+                vec![unscoped.to_string()]
+            }
+            BinSpec::NamedFile { name, .. } => vec![name.clone()],
+            BinSpec::Map { entries } => entries.iter().map(|(name, _)| name.clone()).collect(),
+            BinSpec::None | BinSpec::Dir { .. } | BinSpec::Unknown(_) => Vec::new(),
+        }
+    }
+}
+
+/// Lifecycle scripts Bun runs for a package at install time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub struct LifecycleScripts {
+    pub preinstall: Option<String>,
+    pub install: Option<String>,
+    pub postinstall: Option<String>,
+    pub preprepare: Option<String>,
+    pub prepare: Option<String>,
+    pub postprepare: Option<String>,
+}
+
+impl LifecycleScripts {
+    /// Non-empty scripts as `(hook, command)` in execution order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("preinstall", &self.preinstall),
+            ("install", &self.install),
+            ("postinstall", &self.postinstall),
+            ("preprepare", &self.preprepare),
+            ("prepare", &self.prepare),
+            ("postprepare", &self.postprepare),
+        ]
+        .into_iter()
+        .filter_map(|(hook, cmd)| cmd.as_deref().map(|c| (hook, c)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct DependencyEntry {
     pub name: String,
     pub req: String,
+    /// `req` decoded by the binary record's version tag, or inferred from
+    /// the literal where there is none (text lockfiles, the builder).
+    #[serde(default)]
+    pub spec: DependencySpec,
     pub behavior: BehaviorFlags,
     pub resolved_package_id: Option<u32>,
 }
 
+/// What a dependency's version literal asks for, after Bun's
+/// `Dependency.Version.Tag`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum DependencySpec {
+    /// Tag 0: Bun never resolved the literal.
+    #[default]
+    Uninitialized,
+    /// A semver range such as `^1.2.0`; empty means any version.
+    Npm { range: String },
+    /// A registry dist-tag such as `latest` or `next`.
+    DistTag { tag: String },
+    /// `npm:name@...`: installs `name` under the dependency's name. `target`
+    /// is the `Npm` range or `DistTag` after the `@`.
+    NpmAlias { name: String, target: Box<DependencySpec> },
+    /// An `http(s)://` URL to a `.tgz`.
+    Tarball { url: String },
+    /// A `.tgz` on disk, e.g. `file:./vendor/pkg.tgz`.
+    LocalTarball { path: String },
+    /// `file:` or a relative path to a directory.
+    Folder { path: String },
+    /// `link:path`.
+    Symlink { path: String },
+    /// `workspace:` followed by a range, `*`, `^` or `~`.
+    Workspace { range: String },
+    /// `git+https://`, `git+ssh://`, `git@host:` and similar.
+    Git { url: String, committish: Option<String> },
+    /// `github:owner/repo`, `owner/repo` or a `github.com` URL.
+    Github {
+        owner: String,
+        repo: String,
+        committish: Option<String>,
+    },
+    /// `catalog:` (the default catalog, `name: None`) or `catalog:name`.
+    Catalog { name: Option<String> },
+    Unknown(u8),
+}
+
+impl DependencySpec {
+    /// Decodes `literal` the way Bun's `parseWithTag` does for `tag`.
+    pub fn from_tag(tag: u8, literal: &str) -> DependencySpec {
+        match tag {
+            0 => DependencySpec::Uninitialized,
+            1 | 2 => {
+                if let Some(alias) = npm_alias(literal) {
+                    return alias;
+                }
+                match (tag, literal) {
+                    (1, _) => DependencySpec::Npm { range: literal.to_string() },
+                    (_, "") => DependencySpec::DistTag { tag: "latest".into() },
+                    _ => DependencySpec::DistTag { tag: literal.to_string() },
+                }
+            }
+            3 if is_url(literal) => DependencySpec::Tarball { url: literal.to_string() },
+            3 => DependencySpec::LocalTarball {
+                path: strip(literal, "file:"),
+            },
+            4 => DependencySpec::Folder {
+                path: strip(literal, "file:"),
+            },
+            5 => DependencySpec::Symlink {
+                path: strip(literal, "link:"),
+            },
+            6 => DependencySpec::Workspace {
+                range: strip(literal, "workspace:"),
+            },
+            7 => {
+                let (url, committish) = split_committish(literal);
+                DependencySpec::Git { url, committish }
+            }
+            8 => github(literal).unwrap_or(DependencySpec::Unknown(8)),
+            9 => catalog(literal),
+            other => DependencySpec::Unknown(other),
+        }
+    }
+
+    /// Infers the spec from the literal alone, like Bun's `Tag.infer`.
+    pub fn infer(literal: &str) -> DependencySpec {
+        let literal = literal.trim();
+        if let Some(alias) = npm_alias(literal) {
+            return alias;
+        }
+        let on_disk = |path: &str| {
+            if path.ends_with(".tgz") || path.ends_with(".tar.gz") {
+                DependencySpec::LocalTarball { path: path.to_string() }
+            } else {
+                DependencySpec::Folder { path: path.to_string() }
+            }
+        };
+        if let Some(path) = literal.strip_prefix("file:") {
+            return on_disk(path);
+        }
+        if literal.starts_with("link:") {
+            return DependencySpec::from_tag(5, literal);
+        }
+        if literal.starts_with("workspace:") {
+            return DependencySpec::from_tag(6, literal);
+        }
+        if literal.starts_with("catalog:") {
+            return catalog(literal);
+        }
+        if let Some(spec) = github(literal) {
+            return spec;
+        }
+        let (location, _) = split_committish(literal);
+        let git_prefix = ["git+", "git://", "git@", "ssh://"].iter().any(|p| literal.starts_with(p));
+        if git_prefix || location.ends_with(".git") {
+            return DependencySpec::from_tag(7, literal);
+        }
+        if is_url(literal) {
+            return DependencySpec::Tarball { url: literal.to_string() };
+        }
+        if ["./", "../", "/", "~/"].iter().any(|p| literal.starts_with(p)) {
+            return on_disk(literal);
+        }
+        npm_or_dist_tag(literal)
+    }
+
+    /// The `Dependency.Version.Tag` Bun stores for this spec.
+    pub fn tag(&self) -> u8 {
+        match self {
+            DependencySpec::Uninitialized => 0,
+            DependencySpec::Npm { .. } => 1,
+            DependencySpec::DistTag { .. } => 2,
+            DependencySpec::NpmAlias { target, .. } => target.tag(),
+            DependencySpec::Tarball { .. } | DependencySpec::LocalTarball { .. } => 3,
+            DependencySpec::Folder { .. } => 4,
+            DependencySpec::Symlink { .. } => 5,
+            DependencySpec::Workspace { .. } => 6,
+            DependencySpec::Git { .. } => 7,
+            DependencySpec::Github { .. } => 8,
+            DependencySpec::Catalog { .. } => 9,
+            DependencySpec::Unknown(tag) => *tag,
+        }
+    }
+}
+
+/// An empty literal is `latest`, a range is npm, anything else a dist-tag.
+fn npm_or_dist_tag(literal: &str) -> DependencySpec {
+    if literal.is_empty() {
+        DependencySpec::DistTag { tag: "latest".into() }
+    } else if crate::semver::Range::parse(literal).is_some() {
+        DependencySpec::Npm { range: literal.to_string() }
+    } else {
+        DependencySpec::DistTag { tag: literal.to_string() }
+    }
+}
+
+/// `npm:name@range`, `npm:@scope/name@tag` or a bare `npm:name`.
+fn npm_alias(literal: &str) -> Option<DependencySpec> {
+    let target = literal.strip_prefix("npm:")?;
+    // The version separator is the last `@` that does not start a scope.
+    let (name, version) = match target.rfind('@') {
+        Some(at) if at > 0 => (&target[..at], &target[at + 1..]),
+        _ => (target, ""),
+    };
+    let target = match version {
+        "" => DependencySpec::Npm { range: String::new() },
+        _ => npm_or_dist_tag(version),
+    };
+    Some(DependencySpec::NpmAlias {
+        name: name.to_string(),
+        target: Box::new(target),
+    })
+}
+
+/// `github:owner/repo#ref`, `owner/repo` or `https://github.com/owner/repo.git`.
+fn github(literal: &str) -> Option<DependencySpec> {
+    let (location, committish) = split_committish(literal);
+    let path = if let Some(rest) = location.strip_prefix("github:") {
+        rest
+    } else if let Some(at) = location.find("github.com") {
+        location[at + "github.com".len()..].trim_start_matches([':', '/'])
+    } else if !location.contains(':') && !location.starts_with(['.', '/', '@', '~']) {
+        location.as_str()
+    } else {
+        return None;
+    };
+    let (owner, repo) = path.trim_end_matches(".git").split_once('/')?;
+    let valid = |s: &str| {
+        !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+    };
+    (valid(owner) && valid(repo)).then(|| DependencySpec::Github {
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        committish,
+    })
+}
+
+fn catalog(literal: &str) -> DependencySpec {
+    let name = strip(literal, "catalog:");
+    DependencySpec::Catalog {
+        name: (!name.is_empty()).then_some(name),
+    }
+}
+
+fn split_committish(literal: &str) -> (String, Option<String>) {
+    match literal.split_once('#') {
+        Some((url, committish)) => (url.to_string(), Some(committish.to_string())),
+        None => (literal.to_string(), None),
+    }
+}
+
+fn is_url(literal: &str) -> bool {
+    literal.starts_with("http://") || literal.starts_with("https://")
+}
+
+fn strip(literal: &str, prefix: &str) -> String {
+    literal.strip_prefix(prefix).unwrap_or(literal).to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Package {
     pub name: String,
@@ -44,36 +606,480 @@ pub struct Package {
     pub integrity_hash: Option<String>,
     pub resolution: ResolutionKind,
     pub dependencies: Vec<DependencyEntry>,
+    #[serde(default)]
+    pub meta: PackageMeta,
+    #[serde(default)]
+    pub scripts: LifecycleScripts,
+    #[serde(default)]
+    pub bin: BinSpec,
+}
+
+/// On-disk flavour a [`Lockfile`] was decoded from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LockfileFormat {
+    /// Binary `bun.lockb`.
+    #[default]
+    Binary,
+    /// Text `bun.lock` (JSONC).
+    Text,
+}
+
+/// A column of the binary package table, in on-disk order.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageColumn {
+    Name,
+    NameHash,
+    Resolution,
+    Dependencies,
+    Resolutions,
+    Meta,
+    Bin,
+    Scripts,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Lockfile {
     pub format_version: u32,
+    pub source_format: LockfileFormat,
     pub meta_hash: [u8; 32],
     pub packages: Vec<Package>,
     pub trailers: TrailerInfo,
+    #[serde(default)]
+    pub install_tree: InstallTree,
+    /// Meta hash recomputed from `packages`; `None` for text lockfiles, which
+    /// do not carry one.
+    #[serde(default)]
+    pub computed_meta_hash: Option<[u8; 32]>,
+    /// Stored name hashes that do not match the decoded names (binary only).
+    #[serde(default)]
+    pub name_hash_mismatches: Vec<NameHashMismatch>,
+    /// Package table columns present in the binary lockfile; empty for text
+    /// lockfiles.
+    #[serde(default)]
+    pub columns: Vec<PackageColumn>,
+    /// Which bytes of a binary lockfile the parser decoded; empty for text
+    /// lockfiles.
+    #[serde(default)]
+    pub coverage: Coverage,
+    /// Bytes Bun always leaves zeroed that are not (binary only).
+    #[serde(default)]
+    pub non_canonical: Vec<NonCanonical>,
+}
+
+/// A padding, payload tail or string tail that Bun would have written as
+/// zeros; a sign the lockfile was crafted or edited outside Bun.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NonCanonical {
+    /// First non-zero byte.
+    pub offset: u64,
+    /// From the first to the last non-zero byte.
+    pub len: u64,
+    pub package_id: Option<u32>,
+    /// Structure path, e.g. `packages[3].meta.integrity`.
+    pub field: String,
+    pub kind: NonCanonicalKind,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NonCanonicalKind {
+    /// Struct padding or the unused end of a fixed-size value.
+    Padding,
+    /// Bytes after an inline string's zero terminator.
+    InlineStringTail,
+    /// Digest bytes past the length of the integrity algorithm.
+    IntegrityTail,
+}
+
+impl NonCanonicalKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NonCanonicalKind::Padding => "non-zero padding",
+            NonCanonicalKind::InlineStringTail => "garbage after an inline string terminator",
+            NonCanonicalKind::IntegrityTail => "dirty integrity tail",
+        }
+    }
+}
+
+/// Byte ranges of a binary lockfile by the structure decoded from them, and
+/// what is left over.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Coverage {
+    /// Decoded ranges in file order: header, package columns, pointer block,
+    /// buffers, sentinel and trailer sections.
+    pub spans: Vec<ByteSpan>,
+    /// Ranges no span covers.
+    pub slack: Vec<SlackRange>,
+    /// Pairs of spans claiming the same bytes. Bun never writes these.
+    pub overlaps: Vec<(ByteSpan, ByteSpan)>,
+    /// Printable text in `string_bytes` that no decoded field points at.
+    #[serde(default)]
+    pub orphan_strings: Vec<OrphanString>,
+}
+
+/// A printable run in the string buffer outside every referenced range.
+/// Bun only stores strings something points at, so these were put there by
+/// hand.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OrphanString {
+    /// Absolute file offset.
+    pub offset: u64,
+    pub value: String,
+    pub kind: OrphanKind,
+}
+
+/// What an orphaned string looks like.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanKind {
+    Url,
+    /// Pipes, command chaining or a known downloader/interpreter.
+    Shell,
+    /// A long run of base64 alphabet, e.g. an encoded payload.
+    Base64,
+    Text,
+}
+
+impl OrphanKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrphanKind::Url => "url",
+            OrphanKind::Shell => "shell",
+            OrphanKind::Base64 => "base64",
+            OrphanKind::Text => "text",
+        }
+    }
+
+    pub fn classify(text: &str) -> Self {
+        const SHELL: &[&str] = &[
+            "|", "&&", ";", "$(", "`", "curl ", "wget ", "sh -c", "bash ", "node -e", "eval",
+        ];
+        let base64 = |t: &str| {
+            t.len() >= 16
+                && t.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+        };
+        if text.contains("://") {
+            OrphanKind::Url
+        } else if SHELL.iter().any(|s| text.contains(s)) {
+            OrphanKind::Shell
+        } else if base64(text) {
+            OrphanKind::Base64
+        } else {
+            OrphanKind::Text
+        }
+    }
+}
+
+/// A structure and the bytes it was decoded from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ByteSpan {
+    /// Same naming as parse error paths, e.g. `packages.meta` or `buffers.trees`.
+    pub structure: String,
+    pub start: u64,
+    /// Exclusive.
+    pub end: u64,
+}
+
+/// Bytes between decoded structures.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlackRange {
+    pub offset: u64,
+    pub len: u64,
+    /// Shannon entropy in bits per byte, 0 to 8. Text sits around 4–5,
+    /// compressed or encrypted data near 8.
+    pub entropy: f64,
+    pub zeroed: bool,
+}
+
+impl SlackRange {
+    /// Measures `bytes[start..end]`.
+    fn new(bytes: &[u8], start: u64, end: u64) -> Self {
+        let slice = &bytes[start as usize..end as usize];
+        let mut counts = [0u32; 256];
+        for &b in slice {
+            counts[b as usize] += 1;
+        }
+        let total = slice.len() as f64;
+        let entropy = counts
+            .iter()
+            .filter(|&&n| n > 0)
+            .map(|&n| {
+                let p = n as f64 / total;
+                p * (1.0 / p).log2()
+            })
+            .sum();
+        SlackRange {
+            offset: start,
+            len: end - start,
+            entropy,
+            zeroed: counts[0] as usize == slice.len(),
+        }
+    }
+
+    /// Zero fill shorter than Bun's 8-byte alignment, as written between
+    /// buffers.
+    pub fn is_alignment_padding(&self) -> bool {
+        self.zeroed && self.len < 8
+    }
+}
+
+impl Coverage {
+    /// Sorts `spans`, then finds what they leave out of `bytes` and where they
+    /// collide. Spans may run past the end of a truncated file.
+    pub(crate) fn build(bytes: &[u8], mut spans: Vec<ByteSpan>) -> Self {
+        spans.retain(|s| s.end > s.start);
+        spans.sort_by_key(|s| (s.start, s.end));
+        let file_len = bytes.len() as u64;
+
+        let mut slack = Vec::new();
+        let mut overlaps = Vec::new();
+        let mut covered = 0;
+        for (i, span) in spans.iter().enumerate() {
+            let start = span.start.min(file_len);
+            if start > covered {
+                slack.push(SlackRange::new(bytes, covered, start));
+            }
+            covered = covered.max(span.end.min(file_len));
+            for earlier in spans[..i].iter().filter(|e| e.end > span.start) {
+                overlaps.push((earlier.clone(), span.clone()));
+            }
+        }
+        if file_len > covered {
+            slack.push(SlackRange::new(bytes, covered, file_len));
+        }
+        Coverage {
+            spans,
+            slack,
+            overlaps,
+            orphan_strings: Vec::new(),
+        }
+    }
+}
+
+/// A package or dependency record whose stored `name_hash` disagrees with
+/// Bun's hash of its decoded name; a strong sign of a hand-edited lockfile.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NameHashMismatch {
+    pub package_id: u32,
+    /// Index into the package's dependencies when the mismatch is on a
+    /// dependency record rather than the package row.
+    pub dependency_index: Option<u32>,
+    pub name: String,
+    pub stored: u64,
+    pub computed: u64,
+}
+
+impl Lockfile {
+    /// Whether the header meta hash matches the recomputed one. `None` when
+    /// there is nothing to compare (text lockfiles).
+    pub fn meta_hash_matches(&self) -> Option<bool> {
+        self.computed_meta_hash.map(|computed| computed == self.meta_hash)
+    }
+
+    /// For every package id, the workspaces (by package name) whose dependency
+    /// graph reaches it. Empty when the lockfile has no workspace packages.
+    pub fn workspace_membership(&self) -> Vec<BTreeSet<String>> {
+        let mut members = vec![BTreeSet::new(); self.packages.len()];
+        let has_workspaces = self
+            .packages
+            .iter()
+            .any(|p| matches!(p.resolution, ResolutionKind::Workspace { .. }));
+        if !has_workspaces {
+            return members;
+        }
+        for (start, pkg) in self.packages.iter().enumerate() {
+            let label = match pkg.resolution {
+                ResolutionKind::Root if pkg.name.is_empty() => String::from("(root)"),
+                ResolutionKind::Root | ResolutionKind::Workspace { .. } => pkg.name.clone(),
+                _ => continue,
+            };
+            let mut queue = vec![start];
+            while let Some(id) = queue.pop() {
+                if !members[id].insert(label.clone()) {
+                    continue;
+                }
+                for dep in &self.packages[id].dependencies {
+                    let Some(next) = dep.resolved_package_id.map(|n| n as usize) else {
+                        continue;
+                    };
+                    // Another workspace is its own owner; do not walk into it.
+                    let is_other_workspace = next != start
+                        && matches!(
+                            self.packages.get(next).map(|p| &p.resolution),
+                            Some(ResolutionKind::Workspace { .. }) | Some(ResolutionKind::Root)
+                        );
+                    if next < self.packages.len() && !is_other_workspace {
+                        queue.push(next);
+                    }
+                }
+            }
+        }
+        members
+    }
+}
+
+/// The node_modules layout: which package lands at which path on disk.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct InstallTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+/// One `node_modules` directory and the packages hoisted into it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TreeNode {
+    pub id: u32,
+    pub parent: Option<u32>,
+    /// Dependency whose install folder hosts this directory; `None` for the root.
+    pub dependency_id: Option<u32>,
+    /// Directory relative to the project root, e.g. `node_modules/a/node_modules`.
+    pub path: String,
+    pub entries: Vec<InstalledPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    /// Install location, e.g. `node_modules/a/node_modules/b`.
+    pub path: String,
+    /// Index into the dependency buffer (binary lockfiles only).
+    pub dependency_id: Option<u32>,
+    pub package_id: Option<u32>,
+}
+
+impl InstallTree {
+    pub fn entries(&self) -> impl Iterator<Item = &InstalledPackage> {
+        self.nodes.iter().flat_map(|n| n.entries.iter())
+    }
+
+    /// Install location -> entry. When a path is listed twice the first one wins.
+    pub fn by_path(&self) -> HashMap<&str, &InstalledPackage> {
+        let mut map = HashMap::new();
+        for entry in self.entries() {
+            map.entry(entry.path.as_str()).or_insert(entry);
+        }
+        map
+    }
+
+    /// `node_modules` directories Node searches, in order, for a `require` issued
+    /// by the package installed at `install_path` (empty for the project root).
+    pub fn lookup_dirs(install_path: &str) -> Vec<String> {
+        let mut dirs = Vec::new();
+        if install_path.is_empty() {
+            dirs.push("node_modules".to_string());
+            return dirs;
+        }
+        dirs.push(format!("{install_path}/node_modules"));
+        let mut cur = install_path;
+        while let Some(idx) = cur.rfind("node_modules/") {
+            dirs.push(cur[..idx + "node_modules".len()].to_string());
+            if idx == 0 {
+                break;
+            }
+            cur = &cur[..idx - 1];
+        }
+        dirs
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TrailerInfo {
     pub trusted_hashes: Vec<u32>,
+    /// `trusted_hashes` resolved against the lockfile's packages, in the same order.
+    #[serde(default)]
+    pub trusted: Vec<TrustedEntry>,
     pub has_empty_trusted: bool,
     pub overrides: Vec<OverrideEntry>,
     pub patched: Vec<PatchedEntry>,
     pub catalogs: Vec<CatalogGroup>,
     pub default_catalog: Vec<DependencyEntry>,
     pub workspaces_count: usize,
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceEntry>,
+    /// Sections with a tag this parser does not know, in file order.
+    #[serde(default)]
+    pub unknown: Vec<UnknownTrailer>,
+}
+
+/// A trailer section that was skipped because its tag is unknown. Its bytes
+/// run from `offset` (the tag itself) up to the next known tag or the end.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnknownTrailer {
+    /// The 8-byte tag as text, or hex when it is not printable ASCII.
+    pub tag: String,
+    pub offset: u64,
+    pub raw_len: u64,
+}
+
+impl TrailerInfo {
+    /// Resolves every trailer hash to the package it names. Entries whose hash
+    /// matches no package are left with `None`.
+    pub fn resolve_names(&mut self, packages: &[Package]) {
+        let mut by_name: HashMap<u64, &str> = HashMap::new();
+        let mut by_name_version: HashMap<u64, String> = HashMap::new();
+        let mut by_trusted: HashMap<u32, &str> = HashMap::new();
+        for pkg in packages {
+            by_name.insert(name_hash(&pkg.name), &pkg.name);
+            by_trusted.entry(trusted_hash(&pkg.name)).or_insert(&pkg.name);
+            let name_version = format!("{}@{}", pkg.name, pkg.version);
+            by_name_version.insert(name_hash(&name_version), name_version);
+        }
+        let lookup = |hash: u64| by_name.get(&hash).map(|n| n.to_string());
+
+        self.trusted = self
+            .trusted_hashes
+            .iter()
+            .map(|&hash| TrustedEntry {
+                hash,
+                name: by_trusted.get(&hash).map(|n| n.to_string()),
+            })
+            .collect();
+        for entry in &mut self.overrides {
+            entry.name = lookup(entry.name_hash);
+        }
+        for entry in &mut self.patched {
+            entry.name_version = by_name_version.get(&entry.name_version_hash).cloned();
+        }
+        for ws in self.workspaces.iter_mut().filter(|ws| ws.name.is_none()) {
+            ws.name = lookup(ws.name_hash);
+        }
+    }
+}
+
+/// A `trustedDependencies` hash and the package it names.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustedEntry {
+    pub hash: u32,
+    pub name: Option<String>,
+}
+
+/// A workspace declared in the lockfile, keyed by the hash of its package name.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkspaceEntry {
+    pub name_hash: u64,
+    /// Name of the package in the lockfile whose name hashes to `name_hash`.
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// Workspace directory relative to the root, e.g. `packages/web`.
+    pub path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OverrideEntry {
     pub name_hash: u64,
+    /// Package name `name_hash` resolves to, if any package in the lockfile matches.
+    #[serde(default)]
+    pub name: Option<String>,
     pub dependency: DependencyEntry,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatchedEntry {
     pub name_version_hash: u64,
+    /// `name@version` of the patched package, if any package in the lockfile matches.
+    #[serde(default)]
+    pub name_version: Option<String>,
     pub path: String,
     pub patch_hash: Option<u64>,
 }
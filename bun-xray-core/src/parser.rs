@@ -1,16 +1,94 @@
+use crate::canonical::check_canonical;
+use crate::hash::{self, name_hash};
 use crate::model::{
-    BehaviorFlags, CatalogGroup, DependencyEntry, Lockfile, OverrideEntry, Package, PatchedEntry,
-    ResolutionKind, TrailerInfo,
+    ArchFlags, BehaviorFlags, BinSpec, ByteSpan, CatalogGroup, Coverage, DependencyEntry, DependencySpec, InstallTree,
+    InstalledPackage, LifecycleScripts, Lockfile, LockfileFormat, NameHashMismatch, NonCanonicalKind, OrphanKind, OrphanString, OsFlags, OverrideEntry, Package,
+    PackageColumn, PackageMeta, PackageOrigin, PatchedEntry, ResolutionKind, TrailerInfo, TreeNode,
+    UnknownTrailer, WorkspaceEntry,
 };
+use crate::text_lockfile::parse_text_bytes;
 use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use binrw::{binrw, BinRead, BinReaderExt};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fmt;
 use std::fs;
 use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::{Deref, RangeInclusive};
 use std::path::Path;
 use thiserror::Error;
 
-const MAGIC: &[u8; 42] = b"#!/usr/bin/env bun\nbun-lockfile-format-v0\n";
-const SUPPORTED_FORMAT: u32 = 3;
+pub(crate) const MAGIC: &[u8; 42] = b"#!/usr/bin/env bun\nbun-lockfile-format-v0\n";
+/// Newest binary format revision; the writer always emits this layout.
+pub(crate) const CURRENT_FORMAT: u32 = 3;
+
+/// Package table columns in on-disk order. A table with `field_count` n
+/// carries the first n of them.
+pub(crate) const PACKAGE_COLUMNS: [PackageColumn; 8] = [
+    PackageColumn::Name,
+    PackageColumn::NameHash,
+    PackageColumn::Resolution,
+    PackageColumn::Dependencies,
+    PackageColumn::Resolutions,
+    PackageColumn::Meta,
+    PackageColumn::Bin,
+    PackageColumn::Scripts,
+];
+
+/// What tree records and their hoisted lists point at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TreeIds {
+    /// The package owning the `node_modules` directory; hoisted entries are
+    /// package ids.
+    Package,
+    /// The dependency that created the directory; hoisted entries are
+    /// dependency ids.
+    Dependency,
+}
+
+/// How one binary format revision lays out the package table and trees.
+///
+/// Supporting a new revision means adding its entry to [`FORMAT_LAYOUTS`].
+/// If it changes something these fields cannot express, add a field here and
+/// branch on it in `parse_binary_bytes` rather than on the version number.
+#[derive(Debug, Clone)]
+pub(crate) struct FormatLayout {
+    pub(crate) version: u32,
+    /// Accepted package table `field_count` values.
+    pub(crate) field_counts: RangeInclusive<u64>,
+    pub(crate) tree_ids: TreeIds,
+}
+
+pub(crate) const FORMAT_LAYOUTS: &[FormatLayout] = &[
+    // v0: no bin column yet.
+    FormatLayout {
+        version: 0,
+        field_counts: 6..=6,
+        tree_ids: TreeIds::Package,
+    },
+    // v1: adds the bin column.
+    FormatLayout {
+        version: 1,
+        field_counts: 7..=7,
+        tree_ids: TreeIds::Package,
+    },
+    // v2: trees and hoisted lists switch from package ids to dependency ids.
+    FormatLayout {
+        version: 2,
+        field_counts: 7..=7,
+        tree_ids: TreeIds::Dependency,
+    },
+    // v3: adds the optional lifecycle scripts column.
+    FormatLayout {
+        version: CURRENT_FORMAT,
+        field_counts: 7..=8,
+        tree_ids: TreeIds::Dependency,
+    },
+];
+
+pub(crate) fn format_layout(version: u32) -> Option<&'static FormatLayout> {
+    FORMAT_LAYOUTS.iter().find(|layout| layout.version == version)
+}
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -18,8 +96,8 @@ pub enum ParseError {
     InvalidMagic,
     #[error("unsupported lockfile format version {0}")]
     UnsupportedFormat(u32),
-    #[error("outdated lockfile format {0}")]
-    OutdatedFormat(u32),
+    #[error("lockfile format {format} has no {field_count}-column package table")]
+    UnexpectedColumns { format: u32, field_count: u64 },
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("binary parsing error: {0}")]
@@ -30,39 +108,623 @@ pub enum ParseError {
     StringPointer(u32, u32),
     #[error("utf8 error")]
     Utf8,
+    #[error("text lockfile is not valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid text lockfile: {0}")]
+    TextLockfile(String),
+    /// Strict mode refused a file the lenient parser would have accepted.
+    #[error("strict mode: {} parser anomal{}, first: {}", .0.len(), if .0.len() == 1 { "y" } else { "ies" }, .0[0])]
+    Anomalies(Vec<ParseWarning>),
+    /// Any of the above, located in the file.
+    #[error("{path} at offset {offset}: {source}")]
+    At {
+        offset: u64,
+        /// Structure being decoded, e.g. `packages[412].resolution.npm.version.pre`.
+        path: String,
+        source: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// Attaches where decoding failed. Locations are added from the innermost
+    /// structure outwards: an already located error keeps its more precise
+    /// offset and gets `path` prepended.
+    pub(crate) fn at(self, offset: u64, path: impl fmt::Display) -> Self {
+        match self {
+            ParseError::At {
+                offset: inner_offset,
+                path: inner,
+                source,
+            } => {
+                let sep = if inner.starts_with('[') { "" } else { "." };
+                ParseError::At {
+                    offset: inner_offset,
+                    path: format!("{}{}{}", path, sep, inner),
+                    source,
+                }
+            }
+            other => ParseError::At {
+                offset,
+                path: path.to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// Absolute byte offset of the failure, when known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ParseError::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Path of the structure that failed to decode, when known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ParseError::At { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without its location.
+    pub fn kind(&self) -> &ParseError {
+        match self {
+            ParseError::At { source, .. } => source.kind(),
+            other => other,
+        }
+    }
+}
+
+/// How forgiving the parser is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail with [`ParseError::Anomalies`] instead of returning warnings, so a
+    /// partially decoded or tampered lockfile is never mistaken for a clean
+    /// one. Besides every warning, strict mode rejects a stale meta hash,
+    /// name hash mismatches, slack beyond alignment padding, non-canonical
+    /// bytes and overlapping structures.
+    pub strict: bool,
+    /// Salvage what still decodes from a damaged binary lockfile: fields that
+    /// fail are left empty and reported as [`ParseWarning::Damaged`], rows
+    /// past a truncation as [`ParseWarning::ColumnTruncated`]. Only a broken
+    /// header remains fatal. Combined with `strict`, any damage is an error.
+    pub recover: bool,
+}
+
+impl ParseOptions {
+    fn finish(
+        self,
+        parsed: (Lockfile, Vec<ParseWarning>),
+    ) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+        let (lockfile, mut warnings) = parsed;
+        if !self.strict {
+            return Ok((lockfile, warnings));
+        }
+        warnings.extend(tamper_anomalies(&lockfile));
+        if warnings.is_empty() {
+            return Ok((lockfile, warnings));
+        }
+        // Locate the error at the first anomaly so callers can point at it.
+        let first = (warnings[0].offset(), warnings[0].structure());
+        let err = ParseError::Anomalies(warnings);
+        Err(match first {
+            (Some(offset), structure) => err.at(offset, structure),
+            (None, _) => err,
+        })
+    }
+}
+
+/// The byte-level findings strict mode treats like warnings, in file order
+/// after the header.
+fn tamper_anomalies(lockfile: &Lockfile) -> Vec<ParseWarning> {
+    let mut anomalies = Vec::new();
+    if lockfile.meta_hash_matches() == Some(false) {
+        anomalies.push(ParseWarning::MetaHashMismatch {
+            offset: (MAGIC.len() + 4) as u64,
+        });
+    }
+    for mismatch in &lockfile.name_hash_mismatches {
+        anomalies.push(ParseWarning::NameHashMismatch {
+            package: PackageRef {
+                index: mismatch.package_id,
+                name: lockfile.packages[mismatch.package_id as usize].name.clone(),
+            },
+            dependency_index: mismatch.dependency_index,
+            name: mismatch.name.clone(),
+            stored: mismatch.stored,
+            computed: mismatch.computed,
+        });
+    }
+    let coverage = &lockfile.coverage;
+    for slack in coverage.slack.iter().filter(|s| !s.is_alignment_padding()) {
+        anomalies.push(ParseWarning::Slack {
+            offset: slack.offset,
+            len: slack.len,
+        });
+    }
+    for (first, second) in &coverage.overlaps {
+        anomalies.push(ParseWarning::Overlap {
+            offset: second.start,
+            first: first.structure.clone(),
+            second: second.structure.clone(),
+        });
+    }
+    for found in &lockfile.non_canonical {
+        anomalies.push(ParseWarning::NonCanonical {
+            offset: found.offset,
+            len: found.len,
+            field: found.field.clone(),
+            kind: found.kind,
+        });
+    }
+    anomalies
+}
+
+/// `map_err` shorthand for [`ParseError::at`]. Pass `format_args!` for
+/// dynamic paths so nothing is formatted unless decoding fails.
+trait Locate<T> {
+    fn at(self, offset: u64, path: impl fmt::Display) -> Result<T, ParseError>;
+}
+
+impl<T, E: Into<ParseError>> Locate<T> for Result<T, E> {
+    fn at(self, offset: u64, path: impl fmt::Display) -> Result<T, ParseError> {
+        self.map_err(|e| e.into().at(offset, path))
+    }
+}
+
+/// The package a warning is about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageRef {
+    pub index: u32,
+    pub name: String,
+}
+
+/// A recoverable problem the parser worked around. Offsets are absolute byte
+/// positions of the offending record in a binary lockfile; `Display` gives
+/// the human-readable message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ParseWarning {
+    DependencySliceOutOfBounds {
+        offset: u64,
+        package: PackageRef,
+        off: u32,
+        len: u32,
+        buf_len: usize,
+    },
+    ResolutionSliceOutOfBounds {
+        offset: u64,
+        package: PackageRef,
+        off: u32,
+        len: u32,
+        buf_len: usize,
+    },
+    ResolvedIdOutOfRange {
+        offset: u64,
+        package: PackageRef,
+        dependency: String,
+        id: u32,
+        package_count: usize,
+    },
+    BinMapOutOfBounds {
+        offset: u64,
+        package: PackageRef,
+        off: u32,
+        len: u32,
+        buf_len: usize,
+    },
+    TreeIdMismatch {
+        offset: u64,
+        tree: u32,
+        id: u32,
+    },
+    TreeParentInvalid {
+        offset: u64,
+        tree: u32,
+        parent: u32,
+    },
+    TreeOwnerMissing {
+        offset: u64,
+        tree: u32,
+        parent: u32,
+    },
+    TreeOwnerOutOfRange {
+        offset: u64,
+        tree: u32,
+        id_kind: &'static str,
+        id: u32,
+        count: usize,
+    },
+    HoistedSliceOutOfBounds {
+        offset: u64,
+        tree: u32,
+        off: u32,
+        len: u32,
+        buf_len: usize,
+    },
+    HoistedIdOutOfRange {
+        offset: u64,
+        tree: u32,
+        id_kind: &'static str,
+        id: u32,
+        count: usize,
+    },
+    WorkspaceTrailerMismatch {
+        offset: u64,
+        version_hashes: usize,
+        versions: usize,
+        path_hashes: usize,
+        paths: usize,
+    },
+    UnknownTrailer {
+        offset: u64,
+        tag: String,
+        raw_len: u64,
+    },
+    /// A known section found by scanning past an unknown one. Bun writes
+    /// none of them after a section it does not know, so it may have been
+    /// placed there to hide what precedes it.
+    TrailerAfterUnknown {
+        offset: u64,
+        tag: String,
+        unknown: String,
+    },
+    /// Recovery mode: rows past the end of the file were decoded as empty.
+    ColumnTruncated {
+        offset: u64,
+        column: PackageColumn,
+        decoded: usize,
+        expected: usize,
+    },
+    /// Recovery mode: a structure failed to decode and was replaced by an
+    /// empty value (or dropped, for a dependency).
+    Damaged {
+        offset: u64,
+        path: String,
+        package: Option<PackageRef>,
+        error: String,
+    },
+    /// Strict mode: the header meta hash is not the hash of the packages.
+    MetaHashMismatch {
+        offset: u64,
+    },
+    /// Strict mode: a stored name hash is not the hash of its name.
+    NameHashMismatch {
+        package: PackageRef,
+        dependency_index: Option<u32>,
+        name: String,
+        stored: u64,
+        computed: u64,
+    },
+    /// Strict mode: bytes no structure covers, other than alignment padding.
+    Slack {
+        offset: u64,
+        len: u64,
+    },
+    /// Strict mode: two structures claim the same bytes.
+    Overlap {
+        offset: u64,
+        first: String,
+        second: String,
+    },
+    /// Strict mode: bytes Bun always leaves zeroed that are not.
+    NonCanonical {
+        offset: u64,
+        len: u64,
+        field: String,
+        kind: NonCanonicalKind,
+    },
+    TextEntryNotArray {
+        key: String,
+    },
+    TextEntryMalformed {
+        key: String,
+    },
+    TextDependencyUnresolved {
+        package: PackageRef,
+        dependency: String,
+    },
+}
+
+impl ParseWarning {
+    /// Records a located decoding error skipped in recovery mode.
+    fn damaged(err: &ParseError, package: Option<&PackageRef>) -> Self {
+        ParseWarning::Damaged {
+            offset: err.offset().unwrap_or_default(),
+            path: err.path().unwrap_or_default().to_string(),
+            package: package.cloned(),
+            error: err.kind().to_string(),
+        }
+    }
+
+    /// Stable identifier, the same string as the serialized `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseWarning::DependencySliceOutOfBounds { .. } => "dependency_slice_out_of_bounds",
+            ParseWarning::ResolutionSliceOutOfBounds { .. } => "resolution_slice_out_of_bounds",
+            ParseWarning::ResolvedIdOutOfRange { .. } => "resolved_id_out_of_range",
+            ParseWarning::BinMapOutOfBounds { .. } => "bin_map_out_of_bounds",
+            ParseWarning::TreeIdMismatch { .. } => "tree_id_mismatch",
+            ParseWarning::TreeParentInvalid { .. } => "tree_parent_invalid",
+            ParseWarning::TreeOwnerMissing { .. } => "tree_owner_missing",
+            ParseWarning::TreeOwnerOutOfRange { .. } => "tree_owner_out_of_range",
+            ParseWarning::HoistedSliceOutOfBounds { .. } => "hoisted_slice_out_of_bounds",
+            ParseWarning::HoistedIdOutOfRange { .. } => "hoisted_id_out_of_range",
+            ParseWarning::WorkspaceTrailerMismatch { .. } => "workspace_trailer_mismatch",
+            ParseWarning::UnknownTrailer { .. } => "unknown_trailer",
+            ParseWarning::TrailerAfterUnknown { .. } => "trailer_after_unknown",
+            ParseWarning::ColumnTruncated { .. } => "column_truncated",
+            ParseWarning::Damaged { .. } => "damaged",
+            ParseWarning::MetaHashMismatch { .. } => "meta_hash_mismatch",
+            ParseWarning::NameHashMismatch { .. } => "name_hash_mismatch",
+            ParseWarning::Slack { .. } => "slack",
+            ParseWarning::Overlap { .. } => "overlap",
+            ParseWarning::NonCanonical { .. } => "non_canonical",
+            ParseWarning::TextEntryNotArray { .. } => "text_entry_not_array",
+            ParseWarning::TextEntryMalformed { .. } => "text_entry_malformed",
+            ParseWarning::TextDependencyUnresolved { .. } => "text_dependency_unresolved",
+        }
+    }
+
+    /// The on-disk structure that was being decoded.
+    pub fn structure(&self) -> &'static str {
+        match self {
+            ParseWarning::DependencySliceOutOfBounds { .. } => "packages.dependencies",
+            ParseWarning::ResolutionSliceOutOfBounds { .. } => "packages.resolutions",
+            ParseWarning::ResolvedIdOutOfRange { .. } => "buffers.resolutions",
+            ParseWarning::BinMapOutOfBounds { .. } => "packages.bin",
+            ParseWarning::TreeIdMismatch { .. }
+            | ParseWarning::TreeParentInvalid { .. }
+            | ParseWarning::TreeOwnerMissing { .. }
+            | ParseWarning::TreeOwnerOutOfRange { .. }
+            | ParseWarning::HoistedSliceOutOfBounds { .. } => "buffers.trees",
+            ParseWarning::HoistedIdOutOfRange { .. } => "buffers.hoisted_dependencies",
+            ParseWarning::WorkspaceTrailerMismatch { .. } => "trailers.workspaces",
+            ParseWarning::UnknownTrailer { .. } | ParseWarning::TrailerAfterUnknown { .. } => "trailers",
+            ParseWarning::ColumnTruncated { column, .. } => column_structure(*column),
+            ParseWarning::Damaged { path, .. } => root_structure(path),
+            ParseWarning::MetaHashMismatch { .. } => "header",
+            ParseWarning::NameHashMismatch {
+                dependency_index: Some(_),
+                ..
+            } => "buffers.dependencies",
+            ParseWarning::NameHashMismatch { .. } => "packages.name_hash",
+            ParseWarning::Slack { .. } => "lockfile",
+            ParseWarning::Overlap { second, .. } => root_structure(second),
+            ParseWarning::NonCanonical { field, .. } => root_structure(field),
+            ParseWarning::TextEntryNotArray { .. } | ParseWarning::TextEntryMalformed { .. } => {
+                "text.packages"
+            }
+            ParseWarning::TextDependencyUnresolved { .. } => "text.dependencies",
+        }
+    }
+
+    /// Byte offset of the offending record; `None` for text lockfiles.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            ParseWarning::DependencySliceOutOfBounds { offset, .. }
+            | ParseWarning::ResolutionSliceOutOfBounds { offset, .. }
+            | ParseWarning::ResolvedIdOutOfRange { offset, .. }
+            | ParseWarning::BinMapOutOfBounds { offset, .. }
+            | ParseWarning::TreeIdMismatch { offset, .. }
+            | ParseWarning::TreeParentInvalid { offset, .. }
+            | ParseWarning::TreeOwnerMissing { offset, .. }
+            | ParseWarning::TreeOwnerOutOfRange { offset, .. }
+            | ParseWarning::HoistedSliceOutOfBounds { offset, .. }
+            | ParseWarning::HoistedIdOutOfRange { offset, .. }
+            | ParseWarning::WorkspaceTrailerMismatch { offset, .. }
+            | ParseWarning::UnknownTrailer { offset, .. }
+            | ParseWarning::TrailerAfterUnknown { offset, .. }
+            | ParseWarning::ColumnTruncated { offset, .. }
+            | ParseWarning::Damaged { offset, .. }
+            | ParseWarning::MetaHashMismatch { offset }
+            | ParseWarning::Slack { offset, .. }
+            | ParseWarning::Overlap { offset, .. }
+            | ParseWarning::NonCanonical { offset, .. } => Some(*offset),
+            ParseWarning::NameHashMismatch { .. }
+            | ParseWarning::TextEntryNotArray { .. }
+            | ParseWarning::TextEntryMalformed { .. }
+            | ParseWarning::TextDependencyUnresolved { .. } => None,
+        }
+    }
+
+    pub fn package(&self) -> Option<&PackageRef> {
+        match self {
+            ParseWarning::DependencySliceOutOfBounds { package, .. }
+            | ParseWarning::ResolutionSliceOutOfBounds { package, .. }
+            | ParseWarning::ResolvedIdOutOfRange { package, .. }
+            | ParseWarning::BinMapOutOfBounds { package, .. }
+            | ParseWarning::NameHashMismatch { package, .. }
+            | ParseWarning::TextDependencyUnresolved { package, .. } => Some(package),
+            ParseWarning::Damaged { package, .. } => package.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::DependencySliceOutOfBounds { off, len, buf_len, .. } => write!(
+                f,
+                "dependency slice out of bounds off={} len={} buf_len={}",
+                off, len, buf_len
+            ),
+            ParseWarning::ResolutionSliceOutOfBounds { off, len, buf_len, .. } => write!(
+                f,
+                "resolution slice out of bounds off={} len={} buf_len={}",
+                off, len, buf_len
+            ),
+            ParseWarning::ResolvedIdOutOfRange {
+                dependency,
+                id,
+                package_count,
+                ..
+            } => write!(
+                f,
+                "dependency {} resolved package id {} out of range (package_count={})",
+                dependency, id, package_count
+            ),
+            ParseWarning::BinMapOutOfBounds { off, len, buf_len, .. } => write!(
+                f,
+                "bin map slice out of bounds off={} len={} buf_len={}",
+                off, len, buf_len
+            ),
+            ParseWarning::TreeIdMismatch { tree, id, .. } => {
+                write!(f, "tree {} has mismatched id {}", tree, id)
+            }
+            ParseWarning::TreeParentInvalid { tree, parent, .. } => {
+                write!(f, "tree {} has invalid parent {}", tree, parent)
+            }
+            ParseWarning::TreeOwnerMissing { tree, parent, .. } => {
+                write!(f, "tree {} under parent {} has no owner id", tree, parent)
+            }
+            ParseWarning::TreeOwnerOutOfRange {
+                tree,
+                id_kind,
+                id,
+                count,
+                ..
+            } => write!(
+                f,
+                "tree {} {} id {} out of range ({}_count={})",
+                tree, id_kind, id, id_kind, count
+            ),
+            ParseWarning::HoistedSliceOutOfBounds {
+                tree,
+                off,
+                len,
+                buf_len,
+                ..
+            } => write!(
+                f,
+                "tree {} hoisted slice out of bounds off={} len={} buf_len={}",
+                tree, off, len, buf_len
+            ),
+            ParseWarning::HoistedIdOutOfRange {
+                tree,
+                id_kind,
+                id,
+                count,
+                ..
+            } => write!(
+                f,
+                "tree {} hoisted {} id {} out of range ({}_count={})",
+                tree, id_kind, id, id_kind, count
+            ),
+            ParseWarning::WorkspaceTrailerMismatch {
+                version_hashes,
+                versions,
+                path_hashes,
+                paths,
+                ..
+            } => write!(
+                f,
+                "workspace trailer length mismatch: versions {}/{}, paths {}/{}",
+                version_hashes, versions, path_hashes, paths
+            ),
+            ParseWarning::UnknownTrailer {
+                offset, tag, raw_len, ..
+            } => write!(
+                f,
+                "unknown trailer tag {} at offset {} ({} bytes skipped)",
+                tag, offset, raw_len
+            ),
+            ParseWarning::TrailerAfterUnknown { offset, tag, unknown } => write!(
+                f,
+                "trailer tag {} at offset {} follows unknown trailer {}",
+                tag, offset, unknown
+            ),
+            ParseWarning::ColumnTruncated {
+                column,
+                decoded,
+                expected,
+                ..
+            } => write!(
+                f,
+                "{} column truncated: {} of {} rows in the file, the rest decoded as empty",
+                column_structure(*column),
+                decoded,
+                expected
+            ),
+            ParseWarning::Damaged { path, error, .. } => {
+                write!(f, "{} damaged: {}", path, error)
+            }
+            ParseWarning::MetaHashMismatch { .. } => {
+                f.write_str("header meta hash does not match the packages")
+            }
+            ParseWarning::NameHashMismatch {
+                name, stored, computed, ..
+            } => write!(
+                f,
+                "name hash of {:?} is {:#018x}, expected {:#018x}",
+                name, stored, computed
+            ),
+            ParseWarning::Slack { offset, len } => {
+                write!(f, "{} bytes at offset {} belong to no structure", len, offset)
+            }
+            ParseWarning::Overlap { first, second, .. } => {
+                write!(f, "{} and {} overlap", first, second)
+            }
+            ParseWarning::NonCanonical { field, .. } => {
+                write!(f, "{} has non-zero bytes where Bun writes zeros", field)
+            }
+            ParseWarning::TextEntryNotArray { key } => {
+                write!(f, "text lockfile: package entry {:?} is not an array", key)
+            }
+            ParseWarning::TextEntryMalformed { key } => {
+                write!(f, "text lockfile: malformed package entry {:?}", key)
+            }
+            ParseWarning::TextDependencyUnresolved { package, dependency } => write!(
+                f,
+                "text lockfile: dependency {} of {:?} has no matching package entry",
+                dependency, package.name
+            ),
+        }
+    }
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
-struct PackageTableHeader {
-    len: u64,
-    alignment: u64,
-    field_count: u64,
-    begin: u64,
-    end: u64,
+pub(crate) struct PackageTableHeader {
+    pub(crate) len: u64,
+    pub(crate) alignment: u64,
+    pub(crate) field_count: u64,
+    pub(crate) begin: u64,
+    pub(crate) end: u64,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct ExternalSlice {
-    off: u32,
-    len: u32,
+pub(crate) struct ExternalSlice {
+    pub(crate) off: u32,
+    pub(crate) len: u32,
 }
 
 #[binrw]
 #[derive(Debug, Clone, Copy)]
-struct SemverString {
-    bytes: [u8; 8],
+pub(crate) struct SemverString {
+    pub(crate) bytes: [u8; 8],
 }
 
 impl SemverString {
     fn is_inline(&self) -> bool {
         self.bytes[7] & 0x80 == 0
     }
-    fn decode(&self, string_bytes: &[u8]) -> Result<String, ParseError> {
+    /// `(off, len)` into `string_bytes`, or `None` for an inline string.
+    pub(crate) fn external(&self) -> Option<(u32, u32)> {
         if self.is_inline() {
+            return None;
+        }
+        let raw = u64::from_le_bytes(self.bytes);
+        let cleared = raw & !(1u64 << 63);
+        Some(((cleared & 0xFFFF_FFFF) as u32, (cleared >> 32) as u32))
+    }
+    pub(crate) fn decode(&self, string_bytes: &StringBytes) -> Result<String, ParseError> {
+        let Some((off, len)) = self.external() else {
             let end = self
                 .bytes
                 .iter()
@@ -71,57 +733,133 @@ impl SemverString {
             return std::str::from_utf8(&self.bytes[..end])
                 .map(|s| s.to_string())
                 .map_err(|_| ParseError::Utf8);
-        }
-        let raw = u64::from_le_bytes(self.bytes);
-        let cleared = raw & !(1u64 << 63);
-        let off = (cleared & 0xFFFF_FFFF) as u32;
-        let len = (cleared >> 32) as u32;
+        };
         let start = off as usize;
         let end = start.checked_add(len as usize).ok_or(ParseError::StringPointer(off, len))?;
         if end > string_bytes.len() {
             return Err(ParseError::StringPointer(off, len));
         }
+        string_bytes.mark(self);
         std::str::from_utf8(&string_bytes[start..end])
             .map(|s| s.to_string())
             .map_err(|_| ParseError::Utf8)
     }
 }
 
+/// Printable runs shorter than this are not reported as orphaned strings.
+const MIN_ORPHAN_LEN: usize = 4;
+
+/// The `string_bytes` buffer. Decoding a [`SemverString`] against it records
+/// the range as referenced, so whatever nothing points at can be found once
+/// the whole file is decoded.
+#[derive(Debug, Default)]
+pub(crate) struct StringBytes {
+    bytes: Vec<u8>,
+    referenced: RefCell<Vec<(usize, usize)>>,
+}
+
+impl Deref for StringBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl StringBytes {
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        StringBytes {
+            bytes,
+            referenced: RefCell::default(),
+        }
+    }
+
+    /// Records `s` as referenced without decoding it, for fields the model
+    /// does not carry. Inline and out-of-range strings are ignored.
+    pub(crate) fn mark(&self, s: &SemverString) {
+        let Some((off, len)) = s.external() else {
+            return;
+        };
+        let (start, end) = (off as usize, off as usize + len as usize);
+        if len > 0 && end <= self.bytes.len() {
+            self.referenced.borrow_mut().push((start, end));
+        }
+    }
+
+    /// Printable runs outside every referenced range; `at` is the buffer's
+    /// offset in the file.
+    pub(crate) fn orphans(&self, at: u64) -> Vec<OrphanString> {
+        let mut referenced = self.referenced.borrow().clone();
+        referenced.sort_unstable();
+        let mut gaps = Vec::new();
+        let mut covered = 0;
+        for (start, end) in referenced.into_iter().chain([(self.bytes.len(), 0)]) {
+            if start > covered {
+                gaps.push((covered, start));
+            }
+            covered = covered.max(end);
+        }
+
+        let mut out = Vec::new();
+        for (start, end) in gaps {
+            let printable = |b: &u8| b.is_ascii_graphic() || *b == b' ';
+            let mut run_start = start;
+            for (i, b) in self.bytes[start..end].iter().chain([&0]).enumerate() {
+                if printable(b) {
+                    continue;
+                }
+                let run_end = start + i;
+                if run_end - run_start >= MIN_ORPHAN_LEN {
+                    let value = String::from_utf8_lossy(&self.bytes[run_start..run_end]).into_owned();
+                    out.push(OrphanString {
+                        offset: at + run_start as u64,
+                        kind: OrphanKind::classify(&value),
+                        value,
+                    });
+                }
+                run_start = run_end + 1;
+            }
+        }
+        out
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct ExternalString {
-    value: SemverString,
-    hash: u64,
+pub(crate) struct ExternalString {
+    pub(crate) value: SemverString,
+    pub(crate) hash: u64,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct SemverVersionTag {
-    pre: ExternalString,
-    build: ExternalString,
+pub(crate) struct SemverVersionTag {
+    pub(crate) pre: ExternalString,
+    pub(crate) build: ExternalString,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct SemverVersion {
-    major: u64,
-    minor: u64,
-    patch: u64,
-    tag: SemverVersionTag,
+pub(crate) struct SemverVersion {
+    pub(crate) major: u64,
+    pub(crate) minor: u64,
+    pub(crate) patch: u64,
+    pub(crate) tag: SemverVersionTag,
 }
 
 impl SemverVersion {
-    fn to_string(&self, strings: &[u8]) -> Result<String, ParseError> {
+    /// `at` is the record's offset, used to locate a bad pre/build string.
+    fn render(&self, strings: &StringBytes, at: u64) -> Result<String, ParseError> {
         let mut out = format!("{}.{}.{}", self.major, self.minor, self.patch);
-        let pre = self.tag.pre.value.decode(strings)?;
+        let pre = self.tag.pre.value.decode(strings).at(at + 24, "pre")?;
         if !pre.is_empty() {
             out.push('-');
             out.push_str(&pre);
         }
-        let build = self.tag.build.value.decode(strings)?;
+        let build = self.tag.build.value.decode(strings).at(at + 40, "build")?;
         if !build.is_empty() {
             out.push('+');
             out.push_str(&build);
@@ -133,15 +871,15 @@ impl SemverVersion {
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
-struct VersionedUrl {
-    url: SemverString,
-    version: SemverVersion,
+pub(crate) struct VersionedUrl {
+    pub(crate) url: SemverString,
+    pub(crate) version: SemverVersion,
 }
 
 #[binrw]
 #[brw(repr = u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ResolutionTag {
+pub(crate) enum ResolutionTag {
     Uninitialized = 0,
     Root = 1,
     Npm = 2,
@@ -158,18 +896,18 @@ enum ResolutionTag {
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
-struct Repository {
-    owner: SemverString,
-    repo: SemverString,
-    committish: SemverString,
-    resolved: SemverString,
-    package_name: SemverString,
+pub(crate) struct Repository {
+    pub(crate) owner: SemverString,
+    pub(crate) repo: SemverString,
+    pub(crate) committish: SemverString,
+    pub(crate) resolved: SemverString,
+    pub(crate) package_name: SemverString,
 }
 
 #[binrw]
 #[derive(Debug, Clone)]
 #[br(import(tag: ResolutionTag))]
-enum ResolutionValue {
+pub(crate) enum ResolutionValue {
     #[br(pre_assert(tag == ResolutionTag::Uninitialized))]
     Uninitialized,
     #[br(pre_assert(tag == ResolutionTag::Root))]
@@ -197,83 +935,140 @@ enum ResolutionValue {
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
-struct Resolution {
-    tag: ResolutionTag,
-    _padding: [u8; 7],
+pub(crate) struct Resolution {
+    pub(crate) tag: ResolutionTag,
+    pub(crate) _padding: [u8; 7],
     #[br(args(tag))]
-    value: ResolutionValue,
+    #[brw(pad_size_to = 64)]
+    pub(crate) value: ResolutionValue,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Integrity {
+    pub(crate) tag: u8,
+    pub(crate) value: [u8; 64],
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct Integrity {
-    tag: u8,
-    value: [u8; 64],
+pub(crate) struct Meta {
+    pub(crate) origin: u8,
+    pub(crate) _padding_origin: u8,
+    pub(crate) arch: u16,
+    pub(crate) os: u16,
+    pub(crate) _padding_os: u16,
+    pub(crate) id: u32,
+    pub(crate) man_dir: SemverString,
+    pub(crate) integrity: Integrity,
+    pub(crate) has_install_script: u8,
+    pub(crate) _padding_integrity: [u8; 2],
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct Meta {
-    origin: u8,
-    _padding_origin: u8,
-    arch: u16,
-    os: u16,
-    _padding_os: u16,
-    id: u32,
-    man_dir: SemverString,
-    integrity: Integrity,
-    has_install_script: u8,
-    _padding_integrity: [u8; 2],
+pub(crate) struct Bin {
+    pub(crate) tag: u8,
+    pub(crate) _pad: [u8; 3],
+    pub(crate) value: [u8; 16],
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct Bin {
-    tag: u8,
-    _pad: [u8; 3],
-    value: [u8; 16],
+pub(crate) struct Scripts {
+    pub(crate) preinstall: SemverString,
+    pub(crate) install: SemverString,
+    pub(crate) postinstall: SemverString,
+    pub(crate) preprepare: SemverString,
+    pub(crate) prepare: SemverString,
+    pub(crate) postprepare: SemverString,
+    pub(crate) filled: u8,
+    pub(crate) _pad: [u8; 7],
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct Scripts {
-    preinstall: SemverString,
-    install: SemverString,
-    postinstall: SemverString,
-    preprepare: SemverString,
-    prepare: SemverString,
-    postprepare: SemverString,
-    filled: u8,
-    #[br(pad_after = 1)]
-    _pad: [u8; 1],
+pub(crate) struct DependencyExternal {
+    pub(crate) name: SemverString,
+    pub(crate) name_hash: u64,
+    pub(crate) behavior: u8,
+    pub(crate) version_tag: u8,
+    pub(crate) version_literal: SemverString,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct DependencyExternal {
-    name: SemverString,
-    name_hash: u64,
-    behavior: u8,
-    version_tag: u8,
-    version_literal: SemverString,
+pub(crate) struct PatchedDepExternal {
+    pub(crate) path: SemverString,
+    pub(crate) _padding: [u8; 7],
+    pub(crate) patchfile_hash_is_null: u8,
+    pub(crate) patch_hash: u64,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone, Copy)]
-struct PatchedDepExternal {
-    path: SemverString,
-    _padding: [u8; 7],
-    patchfile_hash_is_null: u8,
-    patch_hash: u64,
+pub(crate) struct TreeExternal {
+    pub(crate) id: u32,
+    pub(crate) dependency_id: u32,
+    pub(crate) parent: u32,
+    pub(crate) dependencies: ExternalSlice,
+}
+
+/// Marks "no parent" / "no dependency" in tree records.
+pub(crate) const INVALID_ID: u32 = u32::MAX;
+
+// On-disk record sizes. The binrw layouts are packed, so these differ from
+// `size_of` where Rust would pad (a dependency is 26 bytes, not 32).
+pub(crate) const DEPENDENCY_RECORD_SIZE: usize = 26;
+pub(crate) const TREE_RECORD_SIZE: usize = 20;
+pub(crate) const EXTERN_STRING_RECORD_SIZE: usize = 16;
+pub(crate) const BIN_RECORD_SIZE: usize = 20;
+pub(crate) const RESOLUTION_RECORD_SIZE: usize = 72;
+pub(crate) const SCRIPTS_RECORD_SIZE: usize = 56;
+pub(crate) const META_RECORD_SIZE: usize = 88;
+
+pub(crate) fn column_record_size(column: PackageColumn) -> usize {
+    match column {
+        PackageColumn::Name
+        | PackageColumn::NameHash
+        | PackageColumn::Dependencies
+        | PackageColumn::Resolutions => 8,
+        PackageColumn::Resolution => RESOLUTION_RECORD_SIZE,
+        PackageColumn::Meta => META_RECORD_SIZE,
+        PackageColumn::Bin => BIN_RECORD_SIZE,
+        PackageColumn::Scripts => SCRIPTS_RECORD_SIZE,
+    }
+}
+
+/// The top-level structure a decoding path or span name falls under.
+fn root_structure(path: &str) -> &'static str {
+    ["header", "packages", "buffers", "sentinel", "trailers"]
+        .into_iter()
+        .find(|root| path.starts_with(root))
+        .unwrap_or("lockfile")
 }
 
-const BUFFER_KINDS: &[BufferKind] = &[
+pub(crate) fn column_structure(column: PackageColumn) -> &'static str {
+    match column {
+        PackageColumn::Name => "packages.name",
+        PackageColumn::NameHash => "packages.name_hash",
+        PackageColumn::Resolution => "packages.resolution",
+        PackageColumn::Dependencies => "packages.dependencies",
+        PackageColumn::Resolutions => "packages.resolutions",
+        PackageColumn::Meta => "packages.meta",
+        PackageColumn::Bin => "packages.bin",
+        PackageColumn::Scripts => "packages.scripts",
+    }
+}
+
+pub(crate) const BUFFER_KINDS: &[BufferKind] = &[
     BufferKind::Dependencies,
     BufferKind::ExternStrings,
     BufferKind::Trees,
@@ -283,7 +1078,7 @@ const BUFFER_KINDS: &[BufferKind] = &[
 ];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum BufferKind {
+pub(crate) enum BufferKind {
     Dependencies,
     ExternStrings,
     Trees,
@@ -293,7 +1088,29 @@ enum BufferKind {
 }
 
 impl BufferKind {
-    fn alignment(&self) -> usize {
+    /// Name used in error paths.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            BufferKind::Dependencies => "dependencies",
+            BufferKind::ExternStrings => "extern_strings",
+            BufferKind::Trees => "trees",
+            BufferKind::HoistedDependencies => "hoisted_dependencies",
+            BufferKind::Resolutions => "resolutions",
+            BufferKind::StringBytes => "string_bytes",
+        }
+    }
+
+    pub(crate) fn record_size(&self) -> usize {
+        match self {
+            BufferKind::Dependencies => DEPENDENCY_RECORD_SIZE,
+            BufferKind::ExternStrings => EXTERN_STRING_RECORD_SIZE,
+            BufferKind::Trees => TREE_RECORD_SIZE,
+            BufferKind::HoistedDependencies | BufferKind::Resolutions => 4,
+            BufferKind::StringBytes => 1,
+        }
+    }
+
+    pub(crate) fn alignment(&self) -> usize {
         match self {
             BufferKind::Dependencies => 8,
             BufferKind::ExternStrings => 8,
@@ -309,190 +1126,508 @@ pub fn parse_lockfile(path: &Path) -> Result<Lockfile, ParseError> {
     parse_lockfile_with_warnings(path).map(|(lf, _)| lf)
 }
 
-pub fn parse_lockfile_with_warnings(path: &Path) -> Result<(Lockfile, Vec<String>), ParseError> {
+pub fn parse_lockfile_with_warnings(path: &Path) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    parse_lockfile_with_options(path, ParseOptions::default())
+}
+
+/// [`parse_lockfile_with_warnings`] with explicit [`ParseOptions`]; in strict
+/// mode the returned warnings are always empty.
+pub fn parse_lockfile_with_options(
+    path: &Path,
+    options: ParseOptions,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let bytes = fs::read(path)?;
+    options.finish(parse_binary_bytes(&bytes, options.recover)?)
+}
+
+/// Sniffs whether `bytes` look like a binary `bun.lockb` or a text `bun.lock`.
+pub fn detect_format(bytes: &[u8]) -> Option<LockfileFormat> {
+    if bytes.starts_with(MAGIC) {
+        return Some(LockfileFormat::Binary);
+    }
+    let text = bytes.strip_prefix("\u{feff}".as_bytes()).unwrap_or(bytes);
+    match text.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') | Some(b'/') => Some(LockfileFormat::Text),
+        _ => None,
+    }
+}
+
+/// Parses either lockfile format, picking the decoder with [`detect_format`].
+pub fn parse_any_lockfile(path: &Path) -> Result<Lockfile, ParseError> {
+    parse_any_lockfile_with_warnings(path).map(|(lf, _)| lf)
+}
+
+pub fn parse_any_lockfile_with_warnings(
+    path: &Path,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
     let bytes = fs::read(path)?;
-    let mut cursor = Cursor::new(bytes.as_slice());
+    parse_lockfile_from_bytes(&bytes)
+}
+
+/// Parses a lockfile already in memory (a git blob, a tarball entry, an
+/// upload). Either format is accepted, as with [`parse_any_lockfile`].
+pub fn parse_lockfile_from_bytes(bytes: &[u8]) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    parse_lockfile_from_bytes_with_options(bytes, ParseOptions::default())
+}
+
+/// [`parse_lockfile_from_bytes`] with explicit [`ParseOptions`].
+pub fn parse_lockfile_from_bytes_with_options(
+    bytes: &[u8],
+    options: ParseOptions,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let parsed = match detect_format(bytes) {
+        Some(LockfileFormat::Binary) => parse_binary_bytes(bytes, options.recover)?,
+        Some(LockfileFormat::Text) => parse_text_bytes(bytes)?,
+        None => return Err(ParseError::InvalidMagic),
+    };
+    options.finish(parsed)
+}
+
+/// Parses a lockfile from `reader`, starting at its current position. Binary
+/// offsets are relative to that position, so it must be the start of the file.
+pub fn parse_lockfile_from_reader<R: Read + Seek>(
+    reader: &mut R,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let start = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity(end.saturating_sub(start) as usize);
+    reader.read_to_end(&mut bytes)?;
+    parse_lockfile_from_bytes(&bytes)
+}
+
+pub(crate) fn parse_binary_bytes(
+    bytes: &[u8],
+    recover: bool,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let mut cursor = Cursor::new(bytes);
+    let mut warnings = Vec::new();
 
     // Header magic
     let mut magic_buf = [0u8; MAGIC.len()];
-    cursor.read_exact(&mut magic_buf)?;
+    cursor.read_exact(&mut magic_buf).at(0, "header.magic")?;
     if magic_buf != *MAGIC {
-        return Err(ParseError::InvalidMagic);
+        return Err(ParseError::InvalidMagic.at(0, "header.magic"));
     }
 
-    let format_version = cursor.read_le::<u32>()?;
-    if format_version > SUPPORTED_FORMAT {
-        return Err(ParseError::UnsupportedFormat(format_version));
-    }
+    let format_at = cursor.position();
+    let format_version = cursor
+        .read_le::<u32>()
+        .at(format_at, "header.format_version")?;
+    let layout = format_layout(format_version)
+        .ok_or(ParseError::UnsupportedFormat(format_version))
+        .at(format_at, "header.format_version")?;
 
     let mut meta_hash = [0u8; 32];
-    cursor.read_exact(&mut meta_hash)?;
-
-    let total_size = cursor.read_le::<u64>()?;
+    cursor
+        .read_exact(&mut meta_hash)
+        .at(cursor.position(), "header.meta_hash")?;
+
+    let total_size_at = cursor.position();
+    let mut total_size = cursor
+        .read_le::<u64>()
+        .at(total_size_at, "header.total_size")?;
     if total_size as usize > bytes.len() {
-        return Err(ParseError::CorruptOffsets(0, total_size, bytes.len()));
+        let err = ParseError::CorruptOffsets(0, total_size, bytes.len())
+            .at(total_size_at, "header.total_size");
+        // A truncated file: read trailers up to where it ends.
+        total_size = salvage(Err(err), recover, None, &mut warnings, || bytes.len() as u64)?;
     }
 
-    let pkg_header: PackageTableHeader = cursor.read_le()?;
+    let pkg_header_at = cursor.position();
+    let pkg_header: PackageTableHeader =
+        cursor.read_le().at(pkg_header_at, "header.package_table")?;
     if pkg_header.end as usize > bytes.len() {
-        return Err(ParseError::CorruptOffsets(pkg_header.begin, pkg_header.end, bytes.len()));
+        let err = ParseError::CorruptOffsets(pkg_header.begin, pkg_header.end, bytes.len())
+            .at(pkg_header_at, "header.package_table");
+        salvage(Err(err), recover, None, &mut warnings, || ())?;
     }
 
-    if pkg_header.field_count < 7 {
-        return Err(ParseError::OutdatedFormat(format_version));
+    if !layout.field_counts.contains(&pkg_header.field_count) {
+        return Err(ParseError::UnexpectedColumns {
+            format: format_version,
+            field_count: pkg_header.field_count,
+        }
+        .at(pkg_header_at + 16, "header.package_table.field_count"));
     }
+    let columns = &PACKAGE_COLUMNS[..pkg_header.field_count as usize];
 
     // Parse package columns
-    let mut pkg_cursor = Cursor::new(bytes.as_slice());
+    let mut pkg_cursor = Cursor::new(bytes);
     pkg_cursor.seek(SeekFrom::Start(pkg_header.begin))?;
+    let mut len = pkg_header.len as usize;
+    if recover {
+        // A package without even a name record in the file is beyond salvage.
+        len = len.min(bytes.len().saturating_sub(pkg_header.begin as usize) / 8);
+    }
+    let names_at = pkg_cursor.position();
+    let names: Vec<SemverString> =
+        read_array(&mut pkg_cursor, len, PackageColumn::Name, recover, &mut warnings)?;
+    let name_hashes: Vec<u64> =
+        read_array(&mut pkg_cursor, len, PackageColumn::NameHash, recover, &mut warnings)?;
+    let resolutions_at = pkg_cursor.position();
+    let resolutions: Vec<Resolution> =
+        read_array(&mut pkg_cursor, len, PackageColumn::Resolution, recover, &mut warnings)?;
+    let dep_slices_at = pkg_cursor.position();
+    let dep_slices: Vec<ExternalSlice> =
+        read_array(&mut pkg_cursor, len, PackageColumn::Dependencies, recover, &mut warnings)?;
+    let res_slices_at = pkg_cursor.position();
+    let res_slices: Vec<ExternalSlice> =
+        read_array(&mut pkg_cursor, len, PackageColumn::Resolutions, recover, &mut warnings)?;
+    let metas: Vec<Meta> =
+        read_array(&mut pkg_cursor, len, PackageColumn::Meta, recover, &mut warnings)?;
+
+    let bins_at = pkg_cursor.position();
+    let bins: Vec<Bin> = if columns.contains(&PackageColumn::Bin) {
+        read_array(&mut pkg_cursor, len, PackageColumn::Bin, recover, &mut warnings)?
+    } else {
+        vec![]
+    };
 
-    let names: Vec<SemverString> = read_array::<SemverString>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let _name_hashes: Vec<u64> = read_array::<u64>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let resolutions: Vec<Resolution> = read_array::<Resolution>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let dep_slices: Vec<ExternalSlice> = read_array::<ExternalSlice>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let res_slices: Vec<ExternalSlice> = read_array::<ExternalSlice>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let metas: Vec<Meta> = read_array::<Meta>(&mut pkg_cursor, pkg_header.len as usize)?;
-    let _bins: Vec<Bin> = read_array::<Bin>(&mut pkg_cursor, pkg_header.len as usize)?;
-
-    let _scripts: Vec<Scripts> = if pkg_header.field_count == 8 {
-        read_array::<Scripts>(&mut pkg_cursor, pkg_header.len as usize)?
+    let scripts_at = pkg_cursor.position();
+    let scripts: Vec<Scripts> = if columns.contains(&PackageColumn::Scripts) {
+        read_array(&mut pkg_cursor, len, PackageColumn::Scripts, recover, &mut warnings)?
     } else {
         vec![]
     };
 
     // Parse buffers
     let buffers_start = pkg_header.end;
-    let parsed_buffers = parse_buffers(&bytes, buffers_start as usize)?;
+    let parsed_buffers = parse_buffers(bytes, buffers_start as usize, recover, &mut warnings)?;
+
+    // What each structure claims, for the coverage map.
+    let span = |structure: String, start: u64, end: u64| ByteSpan { structure, start, end };
+    let mut spans = vec![span("header".into(), 0, pkg_header_at + 40)];
+    let mut column_at = pkg_header.begin;
+    for &column in columns {
+        let end = column_at.saturating_add(pkg_header.len.saturating_mul(column_record_size(column) as u64));
+        spans.push(span(column_structure(column).into(), column_at, end));
+        column_at = end;
+    }
+    let pointers_len = (parsed_buffers.pointers.len() * 16) as u64;
+    spans.push(span("buffers.pointers".into(), buffers_start, buffers_start.saturating_add(pointers_len)));
+    for (kind, begin, end) in &parsed_buffers.pointers {
+        spans.push(span(format!("buffers.{}", kind.name()), *begin, *end));
+    }
 
     // Move cursor to end of buffers and read sentinel
-    let mut tail_cursor = Cursor::new(bytes.as_slice());
+    let mut tail_cursor = Cursor::new(bytes);
     tail_cursor.seek(SeekFrom::Start(parsed_buffers.end_pos as u64))?;
-    let sentinel = tail_cursor.read_le::<u64>()?;
-    if sentinel != 0 {
-        return Err(ParseError::CorruptOffsets(parsed_buffers.end_pos as u64, sentinel, bytes.len()));
-    }
+    let sentinel_at = parsed_buffers.end_pos as u64;
+    let sentinel = tail_cursor
+        .read_le::<u64>()
+        .at(sentinel_at, "sentinel")
+        .and_then(|sentinel| match sentinel {
+            0 => Ok(()),
+            _ => Err(ParseError::CorruptOffsets(sentinel_at, sentinel, bytes.len())
+                .at(sentinel_at, "sentinel")),
+        });
+    salvage(sentinel, recover, None, &mut warnings, || ())?;
+    spans.push(span("sentinel".into(), sentinel_at, sentinel_at.saturating_add(8)));
 
     // Trailers: best-effort skip
-    let mut warnings = Vec::new();
+    let mut trailer_spans = Vec::new();
     let trailers = parse_trailers(
         &mut tail_cursor,
         total_size,
-        parsed_buffers.string_bytes.as_slice(),
+        &parsed_buffers.string_bytes,
         &mut warnings,
-    )?;
+        &mut trailer_spans,
+    );
+    let trailers = salvage(trailers, recover, None, &mut warnings, TrailerInfo::default)?;
+    for t in trailer_spans {
+        spans.push(span(format!("trailers.{}", t.section), t.start, t.end));
+    }
 
     // Build packages
-    let string_bytes = parsed_buffers.string_bytes.as_slice();
-    let dependencies = parsed_buffers.dependencies;
-    let resolutions_buf = parsed_buffers.resolutions;
-
-    let mut packages = Vec::with_capacity(pkg_header.len as usize);
-    for idx in 0..(pkg_header.len as usize) {
-        let name = names[idx].decode(string_bytes)?;
-
-        let resolution = decode_resolution(&resolutions[idx], string_bytes)?;
+    let string_bytes = &parsed_buffers.string_bytes;
+    let dependencies = &parsed_buffers.dependencies;
+
+    let mut packages = Vec::with_capacity(len);
+    let mut dep_records = Vec::with_capacity(len);
+    for idx in 0..len {
+        let name = names[idx].decode(string_bytes).at(
+            names_at + (idx * 8) as u64,
+            format_args!("packages[{}].name", idx),
+        );
+        let unnamed = PackageRef {
+            index: idx as u32,
+            name: String::new(),
+        };
+        let name = salvage(name, recover, Some(&unnamed), &mut warnings, String::new)?;
+        let package = PackageRef { name, ..unnamed };
+
+        let res_at = resolutions_at + (idx * RESOLUTION_RECORD_SIZE) as u64;
+        let resolution = decode_resolution(&resolutions[idx], string_bytes, res_at)
+            .at(res_at, format_args!("packages[{}].resolution", idx));
+        let resolution = salvage(resolution, recover, Some(&package), &mut warnings, || None)?;
         let integrity_hash = decode_integrity(&metas[idx].integrity);
-        let version = resolution
-            .as_ref()
-            .map(|r| match r {
-                ResolutionKind::Npm { version, .. } => version.clone(),
-                _ => String::new(),
-            })
-            .unwrap_or_default();
+        string_bytes.mark(&metas[idx].man_dir);
+        let version = resolution.as_ref().map(ResolutionKind::version).unwrap_or_default();
         let registry_url = resolution
             .as_ref()
-            .map(|r| match r {
-                ResolutionKind::Npm { registry, .. } => registry.clone(),
-                ResolutionKind::RemoteTarball { url } => url.clone(),
-                ResolutionKind::LocalTarball { path } => path.clone(),
-                ResolutionKind::Git { repo, .. } => repo.clone(),
-                ResolutionKind::Github { owner, repo, .. } => format!("{}/{}", owner, repo),
-                ResolutionKind::Folder { path } => path.clone(),
-                ResolutionKind::Symlink { path } => path.clone(),
-                ResolutionKind::Workspace { name } => name.clone(),
-                ResolutionKind::SingleFileModule { url } => url.clone(),
-                ResolutionKind::Root => String::from("root"),
-                ResolutionKind::Unknown(s) => s.clone(),
-            })
+            .map(ResolutionKind::registry_url)
             .unwrap_or_default();
 
-        let deps = gather_dependencies(
+        let row = RowSite {
+            package,
+            dep_slice_at: dep_slices_at + (idx * size_of::<ExternalSlice>()) as u64,
+            res_slice_at: res_slices_at + (idx * size_of::<ExternalSlice>()) as u64,
+        };
+        let (records, deps): (Vec<usize>, Vec<DependencyEntry>) = gather_dependencies(
             &dep_slices[idx],
             &res_slices[idx],
-            &dependencies,
-            &resolutions_buf,
-            string_bytes,
-            pkg_header.len as usize,
+            &parsed_buffers,
+            len,
+            &row,
+            recover,
             &mut warnings,
-        )?;
+        )?
+        .into_iter()
+        .unzip();
+        dep_records.push(records);
+
+        let scripts = match scripts.get(idx) {
+            Some(s) => {
+                let at = scripts_at + (idx * SCRIPTS_RECORD_SIZE) as u64;
+                let decoded = decode_scripts(s, string_bytes, at)
+                    .at(at, format_args!("packages[{}].scripts", idx));
+                salvage(decoded, recover, Some(&row.package), &mut warnings, Default::default)?
+            }
+            None => LifecycleScripts::default(),
+        };
+        let bin = match bins.get(idx) {
+            Some(b) => {
+                let offset = bins_at + (idx * BIN_RECORD_SIZE) as u64;
+                let decoded = decode_bin(b, &parsed_buffers, &row.package, offset, &mut warnings)
+                    .at(offset, format_args!("packages[{}].bin", idx));
+                salvage(decoded, recover, Some(&row.package), &mut warnings, Default::default)?
+            }
+            None => BinSpec::default(),
+        };
 
         packages.push(Package {
-            name,
+            name: row.package.name,
             version,
             registry_url,
             integrity_hash,
             resolution: resolution.unwrap_or(ResolutionKind::Unknown(String::new())),
             dependencies: deps,
+            meta: decode_meta(&metas[idx]),
+            scripts,
+            bin,
         });
     }
 
+    let computed_meta_hash = Some(hash::meta_hash(&packages));
+    let name_hash_mismatches = verify_name_hashes(&packages, &name_hashes, &dep_records, dependencies);
+
+    let mut trailers = trailers;
+    trailers.resolve_names(&packages);
+
+    let install_tree = build_install_tree(layout.tree_ids, &parsed_buffers, &packages, &mut warnings);
+    let install_tree = salvage(install_tree, recover, None, &mut warnings, InstallTree::default)?;
+
+    let non_canonical = check_canonical(bytes, &pkg_header, columns, len, &parsed_buffers);
+
+    // Every string reference has been decoded by now.
+    let mut coverage = Coverage::build(bytes, spans);
+    coverage.orphan_strings = parsed_buffers.string_bytes.orphans(parsed_buffers.string_bytes_at);
+
     Ok((
         Lockfile {
             format_version,
+            source_format: LockfileFormat::Binary,
             meta_hash,
             packages,
             trailers,
+            install_tree,
+            computed_meta_hash,
+            name_hash_mismatches,
+            columns: columns.to_vec(),
+            coverage,
+            non_canonical,
         },
         warnings,
     ))
 }
 
-fn read_array<T>(cursor: &mut Cursor<&[u8]>, len: usize) -> Result<Vec<T>, ParseError>
+/// Recomputes Bun's name hash for every package row and dependency record and
+/// returns the ones that disagree with the stored value.
+fn verify_name_hashes(
+    packages: &[Package],
+    name_hashes: &[u64],
+    dep_records: &[Vec<usize>],
+    deps_buf: &[DependencyExternal],
+) -> Vec<NameHashMismatch> {
+    let mut out = Vec::new();
+    for (idx, pkg) in packages.iter().enumerate() {
+        let computed = name_hash(&pkg.name);
+        if name_hashes[idx] != computed {
+            out.push(NameHashMismatch {
+                package_id: idx as u32,
+                dependency_index: None,
+                name: pkg.name.clone(),
+                stored: name_hashes[idx],
+                computed,
+            });
+        }
+
+        // Recovery drops damaged dependencies, so each decoded one is checked
+        // against the record it came from rather than by position.
+        for (i, (dep, &record)) in pkg.dependencies.iter().zip(&dep_records[idx]).enumerate() {
+            let record = &deps_buf[record];
+            let computed = name_hash(&dep.name);
+            if record.name_hash != computed {
+                out.push(NameHashMismatch {
+                    package_id: idx as u32,
+                    dependency_index: Some(i as u32),
+                    name: dep.name.clone(),
+                    stored: record.name_hash,
+                    computed,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Reads one package column of `len` records. In recovery mode rows past the
+/// end of the file decode as all-zero records (empty strings, uninitialized
+/// resolution, no bin) and the cursor still ends up after the whole column.
+fn read_array<T>(
+    cursor: &mut Cursor<&[u8]>,
+    len: usize,
+    column: PackageColumn,
+    recover: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<T>, ParseError>
 where
     for<'a> T: BinRead<Args<'a> = ()> + Clone,
 {
-    let mut out = Vec::with_capacity(len);
-    for _ in 0..len {
-        let item: T = cursor.read_le()?;
-        out.push(item);
+    const ZEROED: [u8; META_RECORD_SIZE] = [0; META_RECORD_SIZE];
+    let start = cursor.position();
+    let field = &column_structure(column)["packages.".len()..];
+    // `len` comes from the file; never reserve more rows than the bytes left.
+    let remaining = cursor.get_ref().len().saturating_sub(start as usize);
+    let mut out = Vec::with_capacity(len.min(remaining / column_record_size(column)));
+    for idx in 0..len {
+        let at = cursor.position();
+        match cursor.read_le::<T>() {
+            Ok(item) => out.push(item),
+            Err(_) if recover => {
+                warnings.push(ParseWarning::ColumnTruncated {
+                    offset: at,
+                    column,
+                    decoded: idx,
+                    expected: len,
+                });
+                let empty: T = Cursor::new(&ZEROED[..]).read_le()?;
+                out.resize(len, empty);
+                break;
+            }
+            Err(err) => {
+                return Err(ParseError::from(err).at(at, format_args!("packages[{}].{}", idx, field)))
+            }
+        }
     }
+    cursor.seek(SeekFrom::Start(start + (len * column_record_size(column)) as u64))?;
     Ok(out)
 }
 
+/// In recovery mode, records a failed decode as [`ParseWarning::Damaged`] and
+/// carries on with `fallback`; otherwise passes the error through.
+fn salvage<T>(
+    result: Result<T, ParseError>,
+    recover: bool,
+    package: Option<&PackageRef>,
+    warnings: &mut Vec<ParseWarning>,
+    fallback: impl FnOnce() -> T,
+) -> Result<T, ParseError> {
+    match result {
+        Err(err) if recover => {
+            warnings.push(ParseWarning::damaged(&err, package));
+            Ok(fallback())
+        }
+        other => other,
+    }
+}
+
 #[derive(Debug)]
-struct BuffersParseResult {
-    dependencies: Vec<DependencyExternal>,
-    resolutions: Vec<u32>,
-    string_bytes: Vec<u8>,
-    end_pos: usize,
+pub(crate) struct BuffersParseResult {
+    /// The pointer block as stored, in on-disk order.
+    pub(crate) pointers: Vec<(BufferKind, u64, u64)>,
+    pub(crate) dependencies: Vec<DependencyExternal>,
+    /// Where each buffer begins.
+    pub(crate) dependencies_at: u64,
+    pub(crate) extern_strings_at: u64,
+    pub(crate) trees_at: u64,
+    pub(crate) hoisted_at: u64,
+    pub(crate) resolutions_at: u64,
+    pub(crate) string_bytes_at: u64,
+    pub(crate) extern_strings: Vec<ExternalString>,
+    pub(crate) trees: Vec<TreeExternal>,
+    pub(crate) hoisted: Vec<u32>,
+    pub(crate) resolutions: Vec<u32>,
+    pub(crate) string_bytes: StringBytes,
+    pub(crate) end_pos: usize,
 }
 
-fn parse_buffers(bytes: &[u8], start: usize) -> Result<BuffersParseResult, ParseError> {
+/// In recovery mode an unreadable pointer block leaves every buffer empty and
+/// a buffer running past the end of the file keeps its whole records.
+pub(crate) fn parse_buffers(
+    bytes: &[u8],
+    start: usize,
+    recover: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<BuffersParseResult, ParseError> {
     // order by alignment desc, tie-stable
     let mut kinds: Vec<BufferKind> = BUFFER_KINDS.to_vec();
-    kinds.sort_by(|a, b| b.alignment().cmp(&a.alignment()));
+    kinds.sort_by_key(|k| std::cmp::Reverse(k.alignment()));
 
-    let mut cursor = Cursor::new(bytes.as_ref());
+    let mut cursor = Cursor::new(bytes);
     cursor.seek(SeekFrom::Start(start as u64))?;
 
     let mut locations = Vec::with_capacity(kinds.len());
-    for _ in &kinds {
-        let begin = cursor.read_le::<u64>()?;
-        let end = cursor.read_le::<u64>()?;
-        locations.push((begin, end));
+    for kind in &kinds {
+        let at = cursor.position();
+        let path = format_args!("buffers.{}.pointer", kind.name());
+        let pointer = cursor
+            .read_le::<u64>()
+            .at(at, path)
+            .and_then(|begin| Ok((begin, cursor.read_le::<u64>().at(at + 8, path)?)));
+        locations.push(salvage(pointer, recover, None, warnings, || (0, 0))?);
     }
 
     let mut deps = Vec::new();
+    let mut extern_strings = Vec::new();
+    let mut trees = Vec::new();
+    let mut hoisted = Vec::new();
     let mut res = Vec::new();
     let mut string_bytes = Vec::new();
     let mut max_end = start;
 
-    for (kind, (begin_ref, end_ref)) in kinds.iter().zip(locations.iter()) {
+    for (i, (kind, (begin_ref, end_ref))) in kinds.iter().zip(locations.iter()).enumerate() {
         let begin = *begin_ref;
         let end = *end_ref;
-        if end < begin || end as usize > bytes.len() {
-            return Err(ParseError::CorruptOffsets(begin, end, bytes.len()));
-        }
+        let pointer_at = (start as u64).saturating_add((i * 16) as u64);
+        let corrupt = || {
+            ParseError::CorruptOffsets(begin, end, bytes.len())
+                .at(pointer_at, format_args!("buffers.{}.pointer", kind.name()))
+        };
+        let (begin, end) = if end < begin
+            || end as usize > bytes.len()
+            || !(end - begin).is_multiple_of(kind.record_size() as u64)
+        {
+            salvage(Err(corrupt()), recover, None, warnings, || ())?;
+            if end < begin || begin as usize > bytes.len() {
+                continue;
+            }
+            let whole = (end.min(bytes.len() as u64) - begin) / kind.record_size() as u64;
+            (begin, begin + whole * kind.record_size() as u64)
+        } else {
+            (begin, end)
+        };
         max_end = max_end.max(end as usize);
         if begin == end {
             continue;
@@ -500,81 +1635,233 @@ fn parse_buffers(bytes: &[u8], start: usize) -> Result<BuffersParseResult, Parse
         let slice = &bytes[begin as usize..end as usize];
         match kind {
             BufferKind::Dependencies => {
-                let record_size = std::mem::size_of::<DependencyExternal>();
-                if slice.len() % record_size != 0 {
-                    return Err(ParseError::CorruptOffsets(begin, end, bytes.len()));
-                }
-                let mut dep_cursor = Cursor::new(slice);
-                while (dep_cursor.position() as usize) < slice.len() {
-                    let d: DependencyExternal = dep_cursor.read_le()?;
-                    deps.push(d);
-                }
+                deps = read_records(slice, begin, *kind)?;
+            }
+            BufferKind::Trees => {
+                trees = read_records(slice, begin, *kind)?;
+            }
+            BufferKind::HoistedDependencies => {
+                hoisted = read_records(slice, begin, *kind)?;
             }
             BufferKind::Resolutions => {
-                let mut res_cursor = Cursor::new(slice);
-                while (res_cursor.position() as usize) < slice.len() {
-                    res.push(res_cursor.read_le::<u32>()?);
-                }
+                res = read_records(slice, begin, *kind)?;
             }
             BufferKind::StringBytes => {
                 string_bytes.extend_from_slice(slice);
             }
-            _ => {
-                // skip trees, hoisted, extern_strings for now
+            BufferKind::ExternStrings => {
+                extern_strings = read_records(slice, begin, *kind)?;
             }
         }
     }
 
     let ptr_block_end = cursor.position() as usize;
+    let pointers: Vec<(BufferKind, u64, u64)> = kinds
+        .iter()
+        .zip(&locations)
+        .map(|(kind, (begin, end))| (*kind, *begin, *end))
+        .collect();
+    let begin_of = |wanted: BufferKind| {
+        pointers
+            .iter()
+            .find(|(kind, _, _)| *kind == wanted)
+            .map_or(0, |(_, begin, _)| *begin)
+    };
 
     Ok(BuffersParseResult {
         dependencies: deps,
+        dependencies_at: begin_of(BufferKind::Dependencies),
+        extern_strings_at: begin_of(BufferKind::ExternStrings),
+        trees_at: begin_of(BufferKind::Trees),
+        hoisted_at: begin_of(BufferKind::HoistedDependencies),
+        resolutions_at: begin_of(BufferKind::Resolutions),
+        string_bytes_at: begin_of(BufferKind::StringBytes),
+        extern_strings,
+        trees,
+        hoisted,
         resolutions: res,
-        string_bytes,
+        string_bytes: StringBytes::new(string_bytes),
         end_pos: max_end.max(ptr_block_end),
+        pointers,
     })
 }
 
-fn decode_resolution(res: &Resolution, strings: &[u8]) -> Result<Option<ResolutionKind>, ParseError> {
+/// Reads a buffer as consecutive records; `begin` is its offset in the file.
+fn read_records<T>(slice: &[u8], begin: u64, kind: BufferKind) -> Result<Vec<T>, ParseError>
+where
+    for<'a> T: BinRead<Args<'a> = ()>,
+{
+    let mut cursor = Cursor::new(slice);
+    let mut out = Vec::new();
+    while (cursor.position() as usize) < slice.len() {
+        let at = begin + cursor.position();
+        out.push(
+            cursor
+                .read_le()
+                .at(at, format_args!("buffers.{}[{}]", kind.name(), out.len()))?,
+        );
+    }
+    Ok(out)
+}
+
+/// `at` is the record's offset; errors carry the failing field's offset and
+/// a path relative to the record, e.g. `npm.version.pre`.
+fn decode_resolution(
+    res: &Resolution,
+    strings: &StringBytes,
+    at: u64,
+) -> Result<Option<ResolutionKind>, ParseError> {
+    // The value follows the tag and its padding.
+    let value_at = at + 8;
+    let string =
+        |s: &SemverString, field: u64, path: &str| s.decode(strings).at(value_at + field, path);
+    // Strings the model does not carry are still referenced.
+    if let ResolutionValue::Git(repo) | ResolutionValue::Github(repo) = &res.value {
+        strings.mark(&repo.package_name);
+    }
     let val = match &res.value {
         ResolutionValue::Uninitialized => None,
         ResolutionValue::Root => Some(ResolutionKind::Root),
         ResolutionValue::Npm(vu) => {
-            let version = vu.version.to_string(strings)?;
-            let registry = vu.url.decode(strings)?;
+            let version = vu
+                .version
+                .render(strings, value_at + 8)
+                .at(value_at + 8, "npm.version")?;
+            let registry = string(&vu.url, 0, "npm.url")?;
             Some(ResolutionKind::Npm { version, registry })
         }
         ResolutionValue::Folder(s) => Some(ResolutionKind::Folder {
-            path: s.decode(strings)?,
+            path: string(s, 0, "folder")?,
         }),
         ResolutionValue::LocalTarball(s) => Some(ResolutionKind::LocalTarball {
-            path: s.decode(strings)?,
+            path: string(s, 0, "local_tarball")?,
         }),
         ResolutionValue::RemoteTarball(s) => Some(ResolutionKind::RemoteTarball {
-            url: s.decode(strings)?,
+            url: string(s, 0, "remote_tarball")?,
         }),
         ResolutionValue::Symlink(s) => Some(ResolutionKind::Symlink {
-            path: s.decode(strings)?,
+            path: string(s, 0, "symlink")?,
         }),
         ResolutionValue::Workspace(s) => Some(ResolutionKind::Workspace {
-            name: s.decode(strings)?,
+            name: string(s, 0, "workspace")?,
         }),
         ResolutionValue::SingleFileModule(s) => Some(ResolutionKind::SingleFileModule {
-            url: s.decode(strings)?,
+            url: string(s, 0, "single_file_module")?,
         }),
         ResolutionValue::Git(repo) => Some(ResolutionKind::Git {
-            repo: repo.repo.decode(strings)?,
-            commit: repo.committish.decode(strings)?,
+            owner: string(&repo.owner, 0, "git.owner")?,
+            repo: string(&repo.repo, 8, "git.repo")?,
+            commit: string(&repo.committish, 16, "git.committish")?,
+            resolved: string(&repo.resolved, 24, "git.resolved")?,
         }),
         ResolutionValue::Github(repo) => Some(ResolutionKind::Github {
-            owner: repo.owner.decode(strings)?,
-            repo: repo.repo.decode(strings)?,
-            reference: repo.committish.decode(strings)?,
+            owner: string(&repo.owner, 0, "github.owner")?,
+            repo: string(&repo.repo, 8, "github.repo")?,
+            reference: string(&repo.committish, 16, "github.committish")?,
+            resolved: string(&repo.resolved, 24, "github.resolved")?,
         }),
     };
     Ok(val)
 }
 
+fn decode_meta(meta: &Meta) -> PackageMeta {
+    PackageMeta {
+        origin: PackageOrigin::from(meta.origin),
+        os: OsFlags::from_bits_truncate(meta.os),
+        arch: ArchFlags::from_bits_truncate(meta.arch),
+        id: meta.id,
+        has_install_script: match meta.has_install_script {
+            1 => Some(false),
+            2 => Some(true),
+            _ => None,
+        },
+    }
+}
+
+fn decode_bin(
+    bin: &Bin,
+    buffers: &BuffersParseResult,
+    package: &PackageRef,
+    offset: u64,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<BinSpec, ParseError> {
+    let extern_strings = &buffers.extern_strings;
+    let strings = &buffers.string_bytes;
+    // The value follows the tag and its padding.
+    let string_at = |at: usize, field: &str| -> Result<String, ParseError> {
+        let mut raw = [0u8; 8];
+        raw.copy_from_slice(&bin.value[at..at + 8]);
+        SemverString { bytes: raw }
+            .decode(strings)
+            .at(offset + 4 + at as u64, field)
+    };
+    let spec = match bin.tag {
+        0 => BinSpec::None,
+        1 => BinSpec::File {
+            path: string_at(0, "file")?,
+        },
+        2 => BinSpec::NamedFile {
+            name: string_at(0, "named_file.name")?,
+            path: string_at(8, "named_file.path")?,
+        },
+        3 => BinSpec::Dir {
+            path: string_at(0, "dir")?,
+        },
+        4 => {
+            let off = u32::from_le_bytes(bin.value[0..4].try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(bin.value[4..8].try_into().unwrap()) as usize;
+            let Some(list) = extern_strings.get(off..off.saturating_add(len)) else {
+                warnings.push(ParseWarning::BinMapOutOfBounds {
+                    offset,
+                    package: package.clone(),
+                    off: off as u32,
+                    len: len as u32,
+                    buf_len: extern_strings.len(),
+                });
+                return Ok(BinSpec::Map { entries: vec![] });
+            };
+            let mut entries = Vec::with_capacity(list.len() / 2);
+            for (i, pair) in list.chunks_exact(2).enumerate() {
+                let at =
+                    buffers.extern_strings_at + ((off + 2 * i) * EXTERN_STRING_RECORD_SIZE) as u64;
+                let next = at + EXTERN_STRING_RECORD_SIZE as u64;
+                entries.push((
+                    pair[0]
+                        .value
+                        .decode(strings)
+                        .at(at, format_args!("map[{}].name", i))?,
+                    pair[1]
+                        .value
+                        .decode(strings)
+                        .at(next, format_args!("map[{}].path", i))?,
+                ));
+            }
+            BinSpec::Map { entries }
+        }
+        other => BinSpec::Unknown(other),
+    };
+    Ok(spec)
+}
+
+fn decode_scripts(
+    scripts: &Scripts,
+    strings: &StringBytes,
+    at: u64,
+) -> Result<LifecycleScripts, ParseError> {
+    let decode = |s: &SemverString, field: u64, hook: &str| -> Result<Option<String>, ParseError> {
+        let cmd = s.decode(strings).at(at + field, hook)?;
+        Ok((!cmd.is_empty()).then_some(cmd))
+    };
+    Ok(LifecycleScripts {
+        preinstall: decode(&scripts.preinstall, 0, "preinstall")?,
+        install: decode(&scripts.install, 8, "install")?,
+        postinstall: decode(&scripts.postinstall, 16, "postinstall")?,
+        preprepare: decode(&scripts.preprepare, 24, "preprepare")?,
+        prepare: decode(&scripts.prepare, 32, "prepare")?,
+        postprepare: decode(&scripts.postprepare, 40, "postprepare")?,
+    })
+}
+
 fn decode_integrity(int: &Integrity) -> Option<String> {
     match int.tag {
         0 => None,
@@ -586,22 +1873,35 @@ fn decode_integrity(int: &Integrity) -> Option<String> {
     }
 }
 
+/// A package row being decoded, for locating warnings.
+struct RowSite {
+    package: PackageRef,
+    dep_slice_at: u64,
+    res_slice_at: u64,
+}
+
+/// Decodes a package's dependency slice, pairing each entry with the index of
+/// its record in the dependencies buffer.
 fn gather_dependencies(
     dep_slice: &ExternalSlice,
     res_slice: &ExternalSlice,
-    deps_buf: &[DependencyExternal],
-    res_buf: &[u32],
-    strings: &[u8],
+    buffers: &BuffersParseResult,
     package_count: usize,
-    warnings: &mut Vec<String>,
-) -> Result<Vec<DependencyEntry>, ParseError> {
+    row: &RowSite,
+    recover: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<(usize, DependencyEntry)>, ParseError> {
+    let deps_buf = &buffers.dependencies;
+    let res_buf = &buffers.resolutions;
+    let strings = &buffers.string_bytes;
     if dep_slice.off as usize + dep_slice.len as usize > deps_buf.len() {
-        warnings.push(format!(
-            "dependency slice out of bounds off={} len={} buf_len={}",
-            dep_slice.off,
-            dep_slice.len,
-            deps_buf.len()
-        ));
+        warnings.push(ParseWarning::DependencySliceOutOfBounds {
+            offset: row.dep_slice_at,
+            package: row.package.clone(),
+            off: dep_slice.off,
+            len: dep_slice.len,
+            buf_len: deps_buf.len(),
+        });
         return Ok(vec![]);
     }
     let deps = &deps_buf[dep_slice.off as usize..dep_slice.off as usize + dep_slice.len as usize];
@@ -609,34 +1909,221 @@ fn gather_dependencies(
     let resolved_ids = if res_slice.off as usize + res_slice.len as usize <= res_buf.len() {
         Some(&res_buf[res_slice.off as usize..res_slice.off as usize + res_slice.len as usize])
     } else {
-        warnings.push(format!(
-            "resolution slice out of bounds off={} len={} buf_len={}",
-            res_slice.off,
-            res_slice.len,
-            res_buf.len()
-        ));
+        warnings.push(ParseWarning::ResolutionSliceOutOfBounds {
+            offset: row.res_slice_at,
+            package: row.package.clone(),
+            off: res_slice.off,
+            len: res_slice.len,
+            buf_len: res_buf.len(),
+        });
         None
     };
 
     let mut out = Vec::with_capacity(deps.len());
     for (i, d) in deps.iter().enumerate() {
-        let dep = decode_dep_external(d, resolved_ids.and_then(|ids| ids.get(i)).copied(), strings)?
-            .with_package_guard(package_count, warnings);
-        out.push(dep);
+        let dep_at = buffers.dependencies_at
+            + ((dep_slice.off as usize + i) * DEPENDENCY_RECORD_SIZE) as u64;
+        let offset = buffers.resolutions_at + ((res_slice.off as usize + i) * 4) as u64;
+        let dep = decode_dep_external(
+            d,
+            resolved_ids.and_then(|ids| ids.get(i)).copied(),
+            strings,
+            dep_at,
+        )
+        .at(
+            dep_at,
+            format_args!("packages[{}].dependencies[{}]", row.package.index, i),
+        )
+        .map(Some);
+        // A damaged dependency is dropped rather than kept with a blank name.
+        if let Some(dep) = salvage(dep, recover, Some(&row.package), warnings, || None)? {
+            let record = dep_slice.off as usize + i;
+            out.push((record, dep.with_package_guard(package_count, &row.package, offset, warnings)));
+        }
     }
     Ok(out)
 }
 
+/// What an id in a tree record refers to.
+struct TreeTarget {
+    name: String,
+    dependency_id: Option<u32>,
+    package_id: Option<u32>,
+}
+
+/// Rebuilds node_modules paths from tree records. Each tree is one `node_modules`
+/// directory; its slice into `hoisted` lists what is installed there, as
+/// dependency or package ids depending on `tree_ids`.
+fn build_install_tree(
+    tree_ids: TreeIds,
+    buffers: &BuffersParseResult,
+    packages: &[Package],
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<InstallTree, ParseError> {
+    let trees = &buffers.trees;
+    let hoisted = &buffers.hoisted;
+    let deps_buf = &buffers.dependencies;
+    let res_buf = &buffers.resolutions;
+    let strings = &buffers.string_bytes;
+    let lookup = |id: u32| -> Result<Option<TreeTarget>, ParseError> {
+        match tree_ids {
+            TreeIds::Dependency => {
+                let Some(dep) = deps_buf.get(id as usize) else {
+                    return Ok(None);
+                };
+                let package_id = res_buf
+                    .get(id as usize)
+                    .copied()
+                    .filter(|pkg_id| (*pkg_id as usize) < packages.len());
+                Ok(Some(TreeTarget {
+                    name: dep.name.decode(strings).at(
+                        buffers.dependencies_at + (id as usize * DEPENDENCY_RECORD_SIZE) as u64,
+                        format_args!("buffers.dependencies[{}].name", id),
+                    )?,
+                    dependency_id: Some(id),
+                    package_id,
+                }))
+            }
+            TreeIds::Package => Ok(packages
+                .get(id as usize)
+                .map(|pkg| TreeTarget {
+                    name: pkg.name.clone(),
+                    dependency_id: None,
+                    package_id: Some(id),
+                })),
+        }
+    };
+    let id_kind = match tree_ids {
+        TreeIds::Dependency => "dependency",
+        TreeIds::Package => "package",
+    };
+    let id_count = match tree_ids {
+        TreeIds::Dependency => deps_buf.len(),
+        TreeIds::Package => packages.len(),
+    };
+
+    let mut nodes: Vec<TreeNode> = Vec::with_capacity(trees.len());
+    // node_modules path per tree id; `None` when the tree could not be placed.
+    let mut paths: Vec<Option<String>> = Vec::with_capacity(trees.len());
+    for (idx, tree) in trees.iter().enumerate() {
+        let offset = buffers.trees_at + (idx * TREE_RECORD_SIZE) as u64;
+        let tree_idx = idx as u32;
+        if tree.id != tree_idx {
+            warnings.push(ParseWarning::TreeIdMismatch {
+                offset,
+                tree: tree_idx,
+                id: tree.id,
+            });
+        }
+        let parent = (tree.parent != INVALID_ID).then_some(tree.parent);
+        // Older formats store the owning package here instead.
+        let owner = (tree.dependency_id != INVALID_ID).then_some(tree.dependency_id);
+
+        let path = match (parent, owner) {
+            (None, _) => Some(String::from("node_modules")),
+            (Some(p), Some(owner_id)) => {
+                let parent_path = paths.get(p as usize).and_then(Option::as_ref);
+                match (parent_path, lookup(owner_id)?) {
+                    (Some(parent_path), Some(target)) => {
+                        Some(format!("{}/{}/node_modules", parent_path, target.name))
+                    }
+                    (None, _) => {
+                        warnings.push(ParseWarning::TreeParentInvalid {
+                            offset,
+                            tree: tree_idx,
+                            parent: p,
+                        });
+                        None
+                    }
+                    (_, None) => {
+                        warnings.push(ParseWarning::TreeOwnerOutOfRange {
+                            offset,
+                            tree: tree_idx,
+                            id_kind,
+                            id: owner_id,
+                            count: id_count,
+                        });
+                        None
+                    }
+                }
+            }
+            (Some(p), None) => {
+                warnings.push(ParseWarning::TreeOwnerMissing {
+                    offset,
+                    tree: tree_idx,
+                    parent: p,
+                });
+                None
+            }
+        };
+        paths.push(path.clone());
+        let Some(path) = path else { continue };
+
+        let off = tree.dependencies.off as usize;
+        let len = tree.dependencies.len as usize;
+        let Some(ids) = hoisted.get(off..off + len) else {
+            warnings.push(ParseWarning::HoistedSliceOutOfBounds {
+                offset,
+                tree: tree_idx,
+                off: off as u32,
+                len: len as u32,
+                buf_len: hoisted.len(),
+            });
+            continue;
+        };
+
+        let mut entries = Vec::with_capacity(ids.len());
+        for (j, &id) in ids.iter().enumerate() {
+            let Some(TreeTarget {
+                name,
+                dependency_id,
+                package_id,
+            }) = lookup(id)?
+            else {
+                warnings.push(ParseWarning::HoistedIdOutOfRange {
+                    offset: buffers.hoisted_at + ((off + j) * 4) as u64,
+                    tree: tree_idx,
+                    id_kind,
+                    id,
+                    count: id_count,
+                });
+                continue;
+            };
+            entries.push(InstalledPackage {
+                path: format!("{}/{}", path, name),
+                name,
+                dependency_id,
+                package_id,
+            });
+        }
+
+        nodes.push(TreeNode {
+            id: idx as u32,
+            parent,
+            dependency_id: match tree_ids {
+                TreeIds::Dependency => owner,
+                TreeIds::Package => None,
+            },
+            path,
+            entries,
+        });
+    }
+    Ok(InstallTree { nodes })
+}
+
+/// `at` is the record's offset, used to locate a bad name or version string.
 fn decode_dep_external(
     d: &DependencyExternal,
     resolved_id: Option<u32>,
-    strings: &[u8],
+    strings: &StringBytes,
+    at: u64,
 ) -> Result<DependencyEntry, ParseError> {
-    let name = d.name.decode(strings)?;
-    let req = d.version_literal.decode(strings)?;
+    let name = d.name.decode(strings).at(at, "name")?;
+    let req = d.version_literal.decode(strings).at(at + 18, "version")?;
     let behavior = BehaviorFlags::from_bits_truncate(d.behavior);
     Ok(DependencyEntry {
         name,
+        spec: DependencySpec::from_tag(d.version_tag, &req),
         req,
         behavior,
         resolved_package_id: resolved_id,
@@ -644,17 +2131,35 @@ fn decode_dep_external(
 }
 
 trait GuardPackage {
-    fn with_package_guard(self, package_count: usize, warnings: &mut Vec<String>) -> Self;
+    fn with_package_guard(
+        self,
+        package_count: usize,
+        package: &PackageRef,
+        offset: u64,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Self;
 }
 
 impl GuardPackage for DependencyEntry {
-    fn with_package_guard(mut self, package_count: usize, warnings: &mut Vec<String>) -> Self {
+    fn with_package_guard(
+        mut self,
+        package_count: usize,
+        package: &PackageRef,
+        offset: u64,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Self {
         if let Some(id) = self.resolved_package_id {
-            if id as usize >= package_count {
-                warnings.push(format!(
-                    "resolved package id {} out of range (package_count={})",
-                    id, package_count
-                ));
+            // Bun writes INVALID_ID for edges it left unresolved (e.g. optional deps).
+            if id == INVALID_ID {
+                self.resolved_package_id = None;
+            } else if id as usize >= package_count {
+                warnings.push(ParseWarning::ResolvedIdOutOfRange {
+                    offset,
+                    package: package.clone(),
+                    dependency: self.name.clone(),
+                    id,
+                    package_count,
+                });
                 self.resolved_package_id = None;
             }
         }
@@ -662,110 +2167,296 @@ impl GuardPackage for DependencyEntry {
     }
 }
 
-fn parse_trailers(
+/// Trailer section tags this parser understands.
+const TRAILER_TAGS: [&[u8; 8]; 7] = [
+    b"wOrKsPaC",
+    b"tRuStEDd",
+    b"eMpTrUsT",
+    b"oVeRriDs",
+    b"pAtChEdD",
+    b"cAtAlOgS",
+    b"cNfGvRsN",
+];
+
+/// Where one trailer section sits in the file.
+#[derive(Debug, Clone)]
+pub(crate) struct TrailerSpan {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    pub(crate) section: &'static str,
+    pub(crate) tag: String,
+}
+
+fn trailer_section(tag: u64) -> &'static str {
+    match &tag.to_le_bytes() {
+        b"wOrKsPaC" => "workspaces",
+        b"tRuStEDd" => "trusted",
+        b"eMpTrUsT" => "empty_trusted",
+        b"oVeRriDs" => "overrides",
+        b"pAtChEdD" => "patched",
+        b"cAtAlOgS" => "catalogs",
+        b"cNfGvRsN" => "config_version",
+        _ => "unknown",
+    }
+}
+
+/// Tags are ASCII by convention; anything else is shown as hex.
+pub(crate) fn render_tag(tag: u64) -> String {
+    let bytes = tag.to_le_bytes();
+    if bytes.iter().all(|b| b.is_ascii_graphic()) {
+        String::from_utf8_lossy(&bytes).into_owned()
+    } else {
+        format!("{:#018x}", tag)
+    }
+}
+
+/// Each section read is appended to `spans`, including one that fails to
+/// decode (ending where the failure left the cursor).
+pub(crate) fn parse_trailers(
     cursor: &mut Cursor<&[u8]>,
     total_size: u64,
-    strings: &[u8],
-    warnings: &mut Vec<String>,
+    strings: &StringBytes,
+    warnings: &mut Vec<ParseWarning>,
+    spans: &mut Vec<TrailerSpan>,
 ) -> Result<TrailerInfo, ParseError> {
     let mut info = TrailerInfo::default();
     loop {
         let pos = cursor.position();
-        if pos + 8 > total_size {
+        if pos.saturating_add(8) > total_size {
             break;
         }
-        let tag = cursor.read_le::<u64>()?;
-        match tag {
-            // known tags; skip their payloads using readArray semantics
-            t if t == u64::from_le_bytes(*b"wOrKsPaC") => {
-                let ws_names = read_array_bytes(cursor, strings)?;
-                let ws_versions = read_array_bytes(cursor, strings)?;
-                let ws_paths = read_array_bytes(cursor, strings)?;
-                let ws_paths_strings = read_array_bytes(cursor, strings)?;
-                info.workspaces_count = ws_names as usize;
-                warnings.push(format!(
-                    "workspace trailer: names={}, versions={}, paths={}, path_strings={}",
-                    ws_names, ws_versions, ws_paths, ws_paths_strings
-                ));
+        let tag = cursor.read_le::<u64>().at(pos, "trailers.tag")?;
+        // Zero words pad the file out to its alignment; a run of them is one
+        // padding span rather than a section.
+        if tag == 0 {
+            match spans.last_mut() {
+                Some(last) if last.section == "padding" && last.end == pos => last.end = pos + 8,
+                _ => spans.push(TrailerSpan {
+                    start: pos,
+                    end: pos + 8,
+                    section: "padding",
+                    tag: render_tag(tag),
+                }),
             }
-            t if t == u64::from_le_bytes(*b"tRuStEDd") => {
-                let count = read_array_u32(cursor)?;
-                info.trusted_hashes = count;
-            }
-            t if t == u64::from_le_bytes(*b"eMpTrUsT") => {
-                info.has_empty_trusted = true;
-            }
-            t if t == u64::from_le_bytes(*b"oVeRriDs") => {
-                let name_hashes = read_array_u64(cursor)?;
-                let overrides_deps = read_array_dep(cursor, strings)?;
-                let mut entries = Vec::new();
-                for (h, d) in name_hashes.into_iter().zip(overrides_deps.into_iter()) {
-                    entries.push(OverrideEntry {
-                        name_hash: h,
-                        dependency: d,
-                    });
-                }
-                info.overrides = entries;
-            }
-            t if t == u64::from_le_bytes(*b"pAtChEdD") => {
-                let name_version_hashes = read_array_u64(cursor)?;
-                let patched = read_array_patched(cursor, strings)?;
-                let mut entries = Vec::new();
-                for (h, p) in name_version_hashes.into_iter().zip(patched.into_iter()) {
-                    entries.push(PatchedEntry {
-                        name_version_hash: h,
-                        path: p.0,
-                        patch_hash: p.1,
-                    });
-                }
-                info.patched = entries;
-            }
-            t if t == u64::from_le_bytes(*b"cAtAlOgS") => {
-                let _default_names = read_array_strings(cursor, strings)?;
-                let default_deps = read_array_dep(cursor, strings)?;
-                info.default_catalog = default_deps;
-
-                let catalog_names = read_array_strings(cursor, strings)?;
-                let mut groups = Vec::new();
-                for name in catalog_names {
-                    let dep_names = read_array_strings(cursor, strings)?;
-                    let dep_values = read_array_dep(cursor, strings)?;
-                    let deps = dep_names
-                        .into_iter()
-                        .zip(dep_values.into_iter())
-                        .map(|(_n, d)| d)
-                        .collect();
-                    groups.push(CatalogGroup { name, dependencies: deps });
-                }
-                info.catalogs = groups;
+            continue;
+        }
+        let section = read_trailer_section(cursor, pos, tag, total_size, strings, &mut info, warnings);
+        spans.push(TrailerSpan {
+            start: pos,
+            end: cursor.position().max(pos + 8),
+            section: trailer_section(tag),
+            tag: render_tag(tag),
+        });
+        section?;
+    }
+    Ok(info)
+}
+
+/// Reads the payload of the section tagged `tag`, which started at `pos`.
+fn read_trailer_section(
+    cursor: &mut Cursor<&[u8]>,
+    pos: u64,
+    tag: u64,
+    total_size: u64,
+    strings: &StringBytes,
+    info: &mut TrailerInfo,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<(), ParseError> {
+    match tag {
+        // known tags; payloads are readArray ranges into the file
+        t if t == u64::from_le_bytes(*b"wOrKsPaC") => {
+            // versions and paths are two independent hash-keyed maps
+            let version_hashes =
+                read_array_u64(cursor).at(pos, "trailers.workspaces.version_hashes")?;
+            let versions =
+                read_array_versions(cursor, strings).at(pos, "trailers.workspaces.versions")?;
+            let path_hashes =
+                read_array_u64(cursor).at(pos, "trailers.workspaces.path_hashes")?;
+            let paths =
+                read_array_strings(cursor, strings).at(pos, "trailers.workspaces.paths")?;
+            if version_hashes.len() != versions.len() || path_hashes.len() != paths.len() {
+                warnings.push(ParseWarning::WorkspaceTrailerMismatch {
+                    offset: pos,
+                    version_hashes: version_hashes.len(),
+                    versions: versions.len(),
+                    path_hashes: path_hashes.len(),
+                    paths: paths.len(),
+                });
+            }
+            info.workspaces = merge_workspace_maps(
+                version_hashes.into_iter().zip(versions),
+                path_hashes.into_iter().zip(paths),
+            );
+            info.workspaces_count = info.workspaces.len();
+        }
+        t if t == u64::from_le_bytes(*b"tRuStEDd") => {
+            let count = read_array_u32(cursor).at(pos, "trailers.trusted")?;
+            info.trusted_hashes = count;
+        }
+        t if t == u64::from_le_bytes(*b"eMpTrUsT") => {
+            info.has_empty_trusted = true;
+        }
+        t if t == u64::from_le_bytes(*b"oVeRriDs") => {
+            let name_hashes =
+                read_array_u64(cursor).at(pos, "trailers.overrides.name_hashes")?;
+            let overrides_deps =
+                read_array_dep(cursor, strings).at(pos, "trailers.overrides.dependencies")?;
+            let mut entries = Vec::new();
+            for (h, d) in name_hashes.into_iter().zip(overrides_deps) {
+                entries.push(OverrideEntry {
+                    name_hash: h,
+                    name: None,
+                    dependency: d,
+                });
             }
-            t if t == u64::from_le_bytes(*b"cNfGvRsN") => {
-                // config version u64
-                let _ = cursor.read_le::<u64>()?;
+            info.overrides = entries;
+        }
+        t if t == u64::from_le_bytes(*b"pAtChEdD") => {
+            let name_version_hashes =
+                read_array_u64(cursor).at(pos, "trailers.patched.name_version_hashes")?;
+            let patched =
+                read_array_patched(cursor, strings).at(pos, "trailers.patched.entries")?;
+            let mut entries = Vec::new();
+            for (h, p) in name_version_hashes.into_iter().zip(patched) {
+                entries.push(PatchedEntry {
+                    name_version_hash: h,
+                    name_version: None,
+                    path: p.0,
+                    patch_hash: p.1,
+                });
             }
-            _ => {
-                // unknown tag, rewind and stop
-                cursor.seek(SeekFrom::Current(-8))?;
-                break;
+            info.patched = entries;
+        }
+        t if t == u64::from_le_bytes(*b"cAtAlOgS") => {
+            let _default_names = read_array_strings(cursor, strings)
+                .at(pos, "trailers.catalogs.default.names")?;
+            let default_deps = read_array_dep(cursor, strings)
+                .at(pos, "trailers.catalogs.default.dependencies")?;
+            info.default_catalog = default_deps;
+
+            let catalog_names =
+                read_array_strings(cursor, strings).at(pos, "trailers.catalogs.names")?;
+            let mut groups = Vec::new();
+            for name in catalog_names {
+                let dep_names = read_array_strings(cursor, strings)
+                    .at(pos, format_args!("trailers.catalogs.{name}.names"))?;
+                let dep_values = read_array_dep(cursor, strings)
+                    .at(pos, format_args!("trailers.catalogs.{name}.dependencies"))?;
+                let deps = dep_names
+                    .into_iter()
+                    .zip(dep_values)
+                    .map(|(_n, d)| d)
+                    .collect();
+                groups.push(CatalogGroup { name, dependencies: deps });
             }
+            info.catalogs = groups;
+        }
+        t if t == u64::from_le_bytes(*b"cNfGvRsN") => {
+            // config version u64
+            let _ = cursor.read_le::<u64>().at(pos, "trailers.config_version")?;
+        }
+        _ => {
+            // Skip to the next tag we know, or to the end; the payload
+            // layout of an unknown section cannot be guessed. Sections are
+            // runs of u64 words, so only whole words after the unknown tag
+            // are tried: a known tag at any other offset is payload.
+            let bytes = *cursor.get_ref();
+            let end = (total_size as usize).min(bytes.len());
+            let resume = (pos as usize + 8..end.saturating_sub(7))
+                .step_by(8)
+                .find(|&at| {
+                    TRAILER_TAGS
+                        .iter()
+                        .any(|known| bytes[at..at + 8] == known[..])
+                });
+            let unknown = UnknownTrailer {
+                tag: render_tag(tag),
+                offset: pos,
+                raw_len: resume.unwrap_or(end) as u64 - pos,
+            };
+            warnings.push(ParseWarning::UnknownTrailer {
+                offset: unknown.offset,
+                tag: unknown.tag.clone(),
+                raw_len: unknown.raw_len,
+            });
+            if let Some(resume) = resume {
+                let next = u64::from_le_bytes(bytes[resume..resume + 8].try_into().unwrap());
+                warnings.push(ParseWarning::TrailerAfterUnknown {
+                    offset: resume as u64,
+                    tag: render_tag(next),
+                    unknown: unknown.tag.clone(),
+                });
+            }
+            info.unknown.push(unknown);
+            cursor.seek(SeekFrom::Start(resume.unwrap_or(end) as u64))?;
         }
     }
-    Ok(info)
+    Ok(())
+}
+
+/// Joins the workspace version and path maps on their name hash, paths first.
+fn merge_workspace_maps(
+    versions: impl IntoIterator<Item = (u64, String)>,
+    paths: impl IntoIterator<Item = (u64, String)>,
+) -> Vec<WorkspaceEntry> {
+    let mut entries: Vec<WorkspaceEntry> = paths
+        .into_iter()
+        .map(|(name_hash, path)| WorkspaceEntry {
+            name_hash,
+            name: None,
+            version: None,
+            path: Some(path),
+        })
+        .collect();
+    for (name_hash, version) in versions {
+        match entries.iter_mut().find(|e| e.name_hash == name_hash) {
+            Some(entry) => entry.version = Some(version),
+            None => entries.push(WorkspaceEntry {
+                name_hash,
+                name: None,
+                version: Some(version),
+                path: None,
+            }),
+        }
+    }
+    entries
 }
 
 fn read_array_range(cursor: &mut Cursor<&[u8]>) -> Result<(u64, u64), ParseError> {
     let start = cursor.read_le::<u64>()?;
     let end = cursor.read_le::<u64>()?;
-    if end < start {
-        return Err(ParseError::CorruptOffsets(start, end, 0));
+    // Checked before the callers size a buffer from it.
+    let file_len = cursor.get_ref().len();
+    if end < start || end > file_len as u64 {
+        return Err(ParseError::CorruptOffsets(start, end, file_len));
     }
     Ok((start, end))
 }
 
-fn read_array_bytes(cursor: &mut Cursor<&[u8]>, _strings: &[u8]) -> Result<u64, ParseError> {
+fn read_array_versions(
+    cursor: &mut Cursor<&[u8]>,
+    strings: &StringBytes,
+) -> Result<Vec<String>, ParseError> {
     let (start, end) = read_array_range(cursor)?;
+    if end == start {
+        return Ok(vec![]);
+    }
+    let len_bytes = (end - start) as usize;
+    cursor.seek(SeekFrom::Start(start))?;
+    let mut data = vec![0u8; len_bytes];
+    cursor.read_exact(&mut data)?;
     cursor.seek(SeekFrom::Start(end))?;
-    Ok((end - start) as u64)
+    let mut cur = Cursor::new(data.as_slice());
+    let mut out = Vec::new();
+    while (cur.position() as usize) < data.len() {
+        let at = start + cur.position();
+        let v: SemverVersion = cur.read_le().at(at, format_args!("[{}]", out.len()))?;
+        out.push(
+            v.render(strings, at)
+                .at(at, format_args!("[{}]", out.len()))?,
+        );
+    }
+    Ok(out)
 }
 
 fn read_array_u32(cursor: &mut Cursor<&[u8]>) -> Result<Vec<u32>, ParseError> {
@@ -781,7 +2472,11 @@ fn read_array_u32(cursor: &mut Cursor<&[u8]>) -> Result<Vec<u32>, ParseError> {
     let mut cur = Cursor::new(data.as_slice());
     let mut out = Vec::new();
     while (cur.position() as usize) < data.len() {
-        out.push(cur.read_le::<u32>()?);
+        let at = start + cur.position();
+        out.push(
+            cur.read_le::<u32>()
+                .at(at, format_args!("[{}]", out.len()))?,
+        );
     }
     Ok(out)
 }
@@ -799,12 +2494,16 @@ fn read_array_u64(cursor: &mut Cursor<&[u8]>) -> Result<Vec<u64>, ParseError> {
     let mut cur = Cursor::new(data.as_slice());
     let mut out = Vec::new();
     while (cur.position() as usize) < data.len() {
-        out.push(cur.read_le::<u64>()?);
+        let at = start + cur.position();
+        out.push(
+            cur.read_le::<u64>()
+                .at(at, format_args!("[{}]", out.len()))?,
+        );
     }
     Ok(out)
 }
 
-fn read_array_strings(cursor: &mut Cursor<&[u8]>, strings: &[u8]) -> Result<Vec<String>, ParseError> {
+fn read_array_strings(cursor: &mut Cursor<&[u8]>, strings: &StringBytes) -> Result<Vec<String>, ParseError> {
     let (start, end) = read_array_range(cursor)?;
     if end == start {
         return Ok(vec![]);
@@ -817,15 +2516,16 @@ fn read_array_strings(cursor: &mut Cursor<&[u8]>, strings: &[u8]) -> Result<Vec<
     let mut cur = Cursor::new(data.as_slice());
     let mut out = Vec::new();
     while (cur.position() as usize) < data.len() {
-        let s: SemverString = cur.read_le()?;
-        out.push(s.decode(strings)?);
+        let at = start + cur.position();
+        let s: SemverString = cur.read_le().at(at, format_args!("[{}]", out.len()))?;
+        out.push(s.decode(strings).at(at, format_args!("[{}]", out.len()))?);
     }
     Ok(out)
 }
 
 fn read_array_dep(
     cursor: &mut Cursor<&[u8]>,
-    strings: &[u8],
+    strings: &StringBytes,
 ) -> Result<Vec<DependencyEntry>, ParseError> {
     let (start, end) = read_array_range(cursor)?;
     if end == start {
@@ -839,8 +2539,10 @@ fn read_array_dep(
     let mut cur = Cursor::new(data.as_slice());
     let mut out = Vec::new();
     while (cur.position() as usize) < data.len() {
-        let d: DependencyExternal = cur.read_le()?;
-        let dep = decode_dep_external(&d, None, strings)?;
+        let at = start + cur.position();
+        let d: DependencyExternal = cur.read_le().at(at, format_args!("[{}]", out.len()))?;
+        let dep =
+            decode_dep_external(&d, None, strings, at).at(at, format_args!("[{}]", out.len()))?;
         out.push(dep);
     }
     Ok(out)
@@ -848,7 +2550,7 @@ fn read_array_dep(
 
 fn read_array_patched(
     cursor: &mut Cursor<&[u8]>,
-    strings: &[u8],
+    strings: &StringBytes,
 ) -> Result<Vec<(String, Option<u64>)>, ParseError> {
     let (start, end) = read_array_range(cursor)?;
     if end == start {
@@ -862,9 +2564,18 @@ fn read_array_patched(
     let mut cur = Cursor::new(data.as_slice());
     let mut out = Vec::new();
     while (cur.position() as usize) < data.len() {
-        let pd: PatchedDepExternal = cur.read_le()?;
-        let path = pd.path.decode(strings)?;
-        let hash = if pd.patchfile_hash_is_null == 0 { None } else { Some(pd.patch_hash) };
+        let at = start + cur.position();
+        let pd: PatchedDepExternal = cur.read_le().at(at, format_args!("[{}]", out.len()))?;
+        let path = pd
+            .path
+            .decode(strings)
+            .at(at, format_args!("[{}].path", out.len()))?;
+        // Bun sets the flag when the patch file has not been hashed yet.
+        let hash = if pd.patchfile_hash_is_null != 0 {
+            None
+        } else {
+            Some(pd.patch_hash)
+        };
         out.push((path, hash));
     }
     Ok(out)
@@ -1,6 +1,9 @@
+use crate::model::{BehaviorFlags, DependencySpec, InstallTree, ResolutionKind};
+use crate::semver::{Range, Version};
 use crate::{Lockfile, Package, PackageJson};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
 pub trait SecurityScanner {
     fn scan(&self, package_json: Option<&PackageJson>) -> ScanResult;
@@ -13,6 +16,127 @@ pub struct ScanResult {
     pub untrusted_registries: Vec<Package>,
     pub integrity_mismatches: Vec<Package>,
     pub suspicious_versions: Vec<Package>,
+    pub hoisting_shadows: Vec<HoistingShadow>,
+    pub lifecycle_scripts: Vec<LifecycleScriptFinding>,
+    pub bin_conflicts: Vec<BinConflict>,
+    pub bin_shadowing: Vec<BinShadow>,
+    pub undeclared_workspaces: Vec<UndeclaredWorkspace>,
+    pub unresolved_trailer_hashes: Vec<UnresolvedTrailerHash>,
+    pub dependency_name_mismatches: Vec<DependencyNameMismatch>,
+    pub range_violations: Vec<RangeViolation>,
+}
+
+/// A dependency edge resolved to a package with another name than the one
+/// requested, e.g. `lodash` pointing at `lodash-evil`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyNameMismatch {
+    pub dependent: Package,
+    pub dependency: String,
+    pub req: String,
+    /// The dependency name, or the target of an `npm:` alias request.
+    pub expected_name: String,
+    pub resolved_package_id: u32,
+    pub resolved: Package,
+}
+
+/// An edge whose npm resolution falls outside the range it requested, e.g.
+/// `^4.17.21` resolved to `4.17.4`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RangeViolation {
+    pub dependent: Package,
+    pub dependency: String,
+    pub req: String,
+    /// The range `req` desugars to, e.g. `>=4.17.21 <5.0.0-0`.
+    pub range: String,
+    pub behavior: BehaviorFlags,
+    pub resolved_package_id: u32,
+    pub resolved: Package,
+}
+
+/// A workspace resolution whose path the workspace trailer does not list.
+#[derive(Debug, Clone, Serialize)]
+pub struct UndeclaredWorkspace {
+    pub package: Package,
+    pub path: String,
+}
+
+impl fmt::Display for UndeclaredWorkspace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Workspace path {} is not declared in the workspace trailer", self.path)
+    }
+}
+
+/// A trailer entry whose hash matches no package in the lockfile.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedTrailerHash {
+    /// `trusted`, `override` or `patched`.
+    pub trailer: &'static str,
+    pub hash: u64,
+    /// Extra context from the entry, e.g. the patch file path.
+    pub detail: Option<String>,
+}
+
+/// Several differently named packages link the same executable name.
+#[derive(Debug, Clone, Serialize)]
+pub struct BinConflict {
+    pub executable: String,
+    pub packages: Vec<Package>,
+}
+
+/// A transitive dependency exposes an executable named like a common system tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct BinShadow {
+    pub executable: String,
+    pub package: Package,
+}
+
+/// A non-empty lifecycle script and the download/exec patterns it contains.
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleScriptFinding {
+    pub package: Package,
+    pub hook: String,
+    pub command: String,
+    pub indicators: Vec<ScriptIndicator>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptIndicator {
+    /// curl/wget/URLs and similar downloaders.
+    NetworkFetch,
+    /// Output piped straight into an interpreter (`curl ... | sh`).
+    PipeToShell,
+    /// base64 decoding or long encoded blobs.
+    EncodedPayload,
+    /// Inline code execution (`node -e`, `eval`, `python -c`).
+    InlineEval,
+}
+
+impl ScriptIndicator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptIndicator::NetworkFetch => "network_fetch",
+            ScriptIndicator::PipeToShell => "pipe_to_shell",
+            ScriptIndicator::EncodedPayload => "encoded_payload",
+            ScriptIndicator::InlineEval => "inline_eval",
+        }
+    }
+}
+
+/// A dependency edge where Node's `node_modules` lookup would load a different
+/// copy than the one the lockfile resolved, e.g. because a malicious copy was
+/// hoisted above the legitimate one.
+#[derive(Debug, Clone, Serialize)]
+pub struct HoistingShadow {
+    /// Package issuing the `require`.
+    pub dependent: Package,
+    /// Install location of the dependent; empty for the project root.
+    pub dependent_path: String,
+    /// Copy the lockfile resolved the edge to.
+    pub expected: Package,
+    /// Copy found first by the `node_modules` walk.
+    pub loaded: Package,
+    pub loaded_path: String,
 }
 
 impl SecurityScanner for Lockfile {
@@ -23,8 +147,18 @@ impl SecurityScanner for Lockfile {
         let mut untrusted_registries = Vec::new();
         let mut integrity_mismatches = Vec::new();
         let mut suspicious_versions = Vec::new();
+        let mut lifecycle_scripts = Vec::new();
 
         for pkg in &self.packages {
+            for (hook, command) in pkg.scripts.iter() {
+                lifecycle_scripts.push(LifecycleScriptFinding {
+                    package: pkg.clone(),
+                    hook: hook.to_string(),
+                    command: command.to_string(),
+                    indicators: script_indicators(command),
+                });
+            }
+
             if let Some(ref deps) = declared {
                 if !deps.contains(&pkg.name) {
                     phantom_dependencies.push(pkg.clone());
@@ -52,8 +186,279 @@ impl SecurityScanner for Lockfile {
             untrusted_registries,
             integrity_mismatches,
             suspicious_versions,
+            hoisting_shadows: find_hoisting_shadows(self),
+            lifecycle_scripts,
+            bin_conflicts: find_bin_conflicts(self),
+            bin_shadowing: find_bin_shadowing(self),
+            undeclared_workspaces: find_undeclared_workspaces(self),
+            unresolved_trailer_hashes: find_unresolved_trailer_hashes(self),
+            dependency_name_mismatches: find_dependency_name_mismatches(self),
+            range_violations: find_range_violations(self),
+        }
+    }
+}
+
+fn find_hoisting_shadows(lockfile: &Lockfile) -> Vec<HoistingShadow> {
+    let by_path = lockfile.install_tree.by_path();
+    if by_path.is_empty() {
+        return Vec::new();
+    }
+
+    // The root requires from the project directory; everything else from where it is installed.
+    let mut requesters: Vec<(u32, &str)> = Vec::new();
+    if lockfile.packages.first().map(|p| &p.resolution) == Some(&ResolutionKind::Root) {
+        requesters.push((0, ""));
+    }
+    for entry in lockfile.install_tree.entries() {
+        if let Some(id) = entry.package_id {
+            requesters.push((id, entry.path.as_str()));
+        }
+    }
+
+    let mut out = Vec::new();
+    for (pkg_id, path) in requesters {
+        let Some(dependent) = lockfile.packages.get(pkg_id as usize) else {
+            continue;
+        };
+        let dirs = InstallTree::lookup_dirs(path);
+        for dep in &dependent.dependencies {
+            let Some(expected_id) = dep.resolved_package_id else {
+                continue;
+            };
+            let loaded = dirs
+                .iter()
+                .find_map(|dir| by_path.get(format!("{}/{}", dir, dep.name).as_str()));
+            let Some(loaded) = loaded else { continue };
+            let Some(loaded_id) = loaded.package_id else {
+                continue;
+            };
+            if loaded_id == expected_id {
+                continue;
+            }
+            let (Some(expected), Some(loaded_pkg)) = (
+                lockfile.packages.get(expected_id as usize),
+                lockfile.packages.get(loaded_id as usize),
+            ) else {
+                continue;
+            };
+            out.push(HoistingShadow {
+                dependent: dependent.clone(),
+                dependent_path: path.to_string(),
+                expected: expected.clone(),
+                loaded: loaded_pkg.clone(),
+                loaded_path: loaded.path.clone(),
+            });
+        }
+    }
+    out
+}
+
+/// Executable names worth protecting from being shadowed in `node_modules/.bin`.
+const WELL_KNOWN_BINS: &[&str] = &[
+    "node", "npm", "npx", "bun", "bunx", "yarn", "pnpm", "corepack", "git", "sh", "bash", "zsh",
+    "env", "sudo", "ssh", "scp", "curl", "wget", "tar", "make", "gcc", "cc", "python", "python3",
+    "docker", "kubectl", "ls", "cp", "mv", "rm", "cat",
+];
+
+fn find_bin_conflicts(lockfile: &Lockfile) -> Vec<BinConflict> {
+    let mut claims: BTreeMap<String, Vec<&Package>> = BTreeMap::new();
+    for pkg in &lockfile.packages {
+        for exe in pkg.bin.executables(&pkg.name) {
+            let owners = claims.entry(exe).or_default();
+            // Several versions of the same package legitimately share their bins.
+            if !owners.iter().any(|p| p.name == pkg.name) {
+                owners.push(pkg);
+            }
+        }
+    }
+    claims
+        .into_iter()
+        .filter(|(_, owners)| owners.len() > 1)
+        .map(|(executable, owners)| BinConflict {
+            executable,
+            packages: owners.into_iter().cloned().collect(),
+        })
+        .collect()
+}
+
+fn find_bin_shadowing(lockfile: &Lockfile) -> Vec<BinShadow> {
+    // Direct dependencies are the ones the root or a workspace asks for by name.
+    let direct: HashSet<u32> = lockfile
+        .packages
+        .iter()
+        .filter(|p| {
+            matches!(
+                p.resolution,
+                ResolutionKind::Root | ResolutionKind::Workspace { .. }
+            )
+        })
+        .flat_map(|p| p.dependencies.iter().filter_map(|d| d.resolved_package_id))
+        .collect();
+
+    let mut out = Vec::new();
+    for (id, pkg) in lockfile.packages.iter().enumerate() {
+        if direct.contains(&(id as u32))
+            || matches!(
+                pkg.resolution,
+                ResolutionKind::Root | ResolutionKind::Workspace { .. }
+            )
+        {
+            continue;
+        }
+        for exe in pkg.bin.executables(&pkg.name) {
+            if WELL_KNOWN_BINS.contains(&exe.as_str()) {
+                out.push(BinShadow {
+                    executable: exe,
+                    package: pkg.clone(),
+                });
+            }
         }
     }
+    out
+}
+
+/// Workspace resolutions whose path is missing from the workspace trailer.
+/// Only meaningful when the lockfile declares workspaces at all.
+fn find_undeclared_workspaces(lockfile: &Lockfile) -> Vec<UndeclaredWorkspace> {
+    let declared: HashSet<&str> = lockfile
+        .trailers
+        .workspaces
+        .iter()
+        .filter_map(|ws| ws.path.as_deref())
+        .collect();
+    if declared.is_empty() {
+        return Vec::new();
+    }
+    lockfile
+        .packages
+        .iter()
+        .filter_map(|p| match &p.resolution {
+            ResolutionKind::Workspace { name: path } if !declared.contains(path.as_str()) => {
+                Some(UndeclaredWorkspace {
+                    package: p.clone(),
+                    path: path.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn find_unresolved_trailer_hashes(lockfile: &Lockfile) -> Vec<UnresolvedTrailerHash> {
+    let trailers = &lockfile.trailers;
+    let trusted = trailers
+        .trusted
+        .iter()
+        .filter(|t| t.name.is_none())
+        .map(|t| UnresolvedTrailerHash {
+            trailer: "trusted",
+            hash: u64::from(t.hash),
+            detail: None,
+        });
+    let overrides = trailers
+        .overrides
+        .iter()
+        .filter(|o| o.name.is_none())
+        .map(|o| UnresolvedTrailerHash {
+            trailer: "override",
+            hash: o.name_hash,
+            detail: Some(format!("{}@{}", o.dependency.name, o.dependency.req)),
+        });
+    let patched = trailers
+        .patched
+        .iter()
+        .filter(|p| p.name_version.is_none())
+        .map(|p| UnresolvedTrailerHash {
+            trailer: "patched",
+            hash: p.name_version_hash,
+            detail: Some(p.path.clone()),
+        });
+    trusted.chain(overrides).chain(patched).collect()
+}
+
+/// Every resolved edge must land on a package named like the dependency, or
+/// like the alias target for `"alias": "npm:real@range"` requests.
+fn find_dependency_name_mismatches(lockfile: &Lockfile) -> Vec<DependencyNameMismatch> {
+    let mut out = Vec::new();
+    for dependent in &lockfile.packages {
+        for dep in &dependent.dependencies {
+            let Some(id) = dep.resolved_package_id else {
+                continue;
+            };
+            let Some(resolved) = lockfile.packages.get(id as usize) else {
+                continue;
+            };
+            let expected = match &dep.spec {
+                DependencySpec::NpmAlias { name, .. } => name.as_str(),
+                _ => dep.name.as_str(),
+            };
+            if resolved.name != expected {
+                out.push(DependencyNameMismatch {
+                    dependent: dependent.clone(),
+                    dependency: dep.name.clone(),
+                    req: dep.req.clone(),
+                    expected_name: expected.to_string(),
+                    resolved_package_id: id,
+                    resolved: resolved.clone(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Checks npm resolutions against the requested range. Overridden names are
+/// skipped: an override replaces the range on purpose. Only npm specs (and
+/// aliases to one) carry a range; dist-tags, git, tarballs and `workspace:`
+/// cannot be checked.
+fn find_range_violations(lockfile: &Lockfile) -> Vec<RangeViolation> {
+    let overridden: HashSet<&str> = lockfile
+        .trailers
+        .overrides
+        .iter()
+        .flat_map(|o| [o.name.as_deref(), Some(o.dependency.name.as_str())])
+        .flatten()
+        .collect();
+    let mut out = Vec::new();
+    for dependent in &lockfile.packages {
+        for dep in &dependent.dependencies {
+            let Some(id) = dep.resolved_package_id else {
+                continue;
+            };
+            let Some(resolved) = lockfile.packages.get(id as usize) else {
+                continue;
+            };
+            if overridden.contains(dep.name.as_str()) {
+                continue;
+            }
+            let ResolutionKind::Npm { version, .. } = &resolved.resolution else {
+                continue;
+            };
+            let req = match &dep.spec {
+                DependencySpec::Npm { range } => range,
+                DependencySpec::NpmAlias { target, .. } => match target.as_ref() {
+                    DependencySpec::Npm { range } => range,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let (Some(range), Some(version)) = (Range::parse(req), Version::parse(version)) else {
+                continue;
+            };
+            if !range.satisfies(&version) {
+                out.push(RangeViolation {
+                    dependent: dependent.clone(),
+                    dependency: dep.name.clone(),
+                    req: dep.req.clone(),
+                    range: range.to_string(),
+                    behavior: dep.behavior,
+                    resolved_package_id: id,
+                    resolved: resolved.clone(),
+                });
+            }
+        }
+    }
+    out
 }
 
 fn build_declared_set(package_json: Option<&PackageJson>) -> Option<HashSet<String>> {
@@ -97,3 +502,120 @@ fn is_version_suspicious(version: &str) -> bool {
         || v.contains('#')
         || v.contains('-')
 }
+
+const DOWNLOADERS: &[&str] = &[
+    "curl",
+    "wget",
+    "nc",
+    "ncat",
+    "iwr",
+    "invoke-webrequest",
+    "invoke-restmethod",
+    "bitsadmin",
+    "certutil",
+];
+const INTERPRETERS: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "dash",
+    "node",
+    "python",
+    "python3",
+    "perl",
+    "iex",
+    "powershell",
+    "pwsh",
+];
+const EVAL_FLAGS: &[(&str, &str)] = &[
+    ("node", "-e"),
+    ("node", "--eval"),
+    ("node", "-p"),
+    ("node", "--print"),
+    ("python", "-c"),
+    ("python3", "-c"),
+    ("perl", "-e"),
+    ("ruby", "-e"),
+    ("bun", "-e"),
+    ("bun", "--eval"),
+];
+
+/// Pipeline stages that read another command's output: everything after a
+/// single `|`. `||` runs its right side only on failure, so it starts a new
+/// pipeline instead.
+fn piped_stages(command: &str) -> impl Iterator<Item = &str> {
+    let mut stages = Vec::new();
+    let (mut start, mut piped) = (0, false);
+    let bytes = command.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'|' {
+            if piped {
+                stages.push(&command[start..i]);
+            }
+            let or = bytes.get(i + 1) == Some(&b'|');
+            piped = !or;
+            i += if or { 2 } else { 1 };
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    if piped {
+        stages.push(&command[start..]);
+    }
+    stages.into_iter()
+}
+
+fn script_indicators(command: &str) -> Vec<ScriptIndicator> {
+    let lower = command.to_ascii_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || "|;&()'\"`".contains(c))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut out = Vec::new();
+
+    if tokens.iter().any(|t| DOWNLOADERS.contains(t))
+        || ["http://", "https://", "ftp://", "fetch("].iter().any(|p| lower.contains(p))
+    {
+        out.push(ScriptIndicator::NetworkFetch);
+    }
+
+    let compact: String = lower.chars().filter(|c| !c.is_whitespace()).collect();
+    let piped = piped_stages(&lower).any(|stage| {
+        let mut words = stage.split_whitespace().skip_while(|w| matches!(*w, "sudo" | "env") || w.contains('='));
+        // `/bin/sh` runs `sh` too.
+        let program = words.next().map(|w| w.rsplit('/').next().unwrap_or(w));
+        program.is_some_and(|p| INTERPRETERS.contains(&p))
+    });
+    if piped || compact.contains("$(curl") || compact.contains("$(wget") {
+        out.push(ScriptIndicator::PipeToShell);
+    }
+
+    let long_blob = command.split_whitespace().any(|t| {
+        let t = t.trim_matches(|c| c == '\'' || c == '"');
+        t.len() >= 40
+            && t
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=')
+            && t.chars().any(|c| c.is_ascii_digit())
+            && t.chars().any(|c| c.is_ascii_uppercase())
+    });
+    if long_blob
+        || ["base64", "atob(", "frombase64string", "-encodedcommand"]
+            .iter()
+            .any(|p| lower.contains(p))
+        || tokens.contains(&"-enc")
+    {
+        out.push(ScriptIndicator::EncodedPayload);
+    }
+
+    let eval_flag = tokens
+        .windows(2)
+        .any(|w| EVAL_FLAGS.iter().any(|(bin, flag)| w[0] == *bin && w[1] == *flag));
+    if eval_flag || lower.contains("eval(") || tokens.contains(&"eval") {
+        out.push(ScriptIndicator::InlineEval);
+    }
+
+    out
+}
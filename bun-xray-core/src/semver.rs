@@ -0,0 +1,415 @@
+//! npm's flavour of semver: versions, ranges and `satisfies`.
+//!
+//! Ranges follow node-semver without `includePrerelease`: `||` separated
+//! comparator sets of primitives (`<`, `<=`, `>`, `>=`, `=`), x-ranges
+//! (`1.x`, `1.2.*`, `*`), tilde, caret and hyphen ranges, all desugared to
+//! primitives. A prerelease version only satisfies a set that names a
+//! prerelease of the same `major.minor.patch`, so `^1.2.3-beta.1` admits
+//! `1.2.3-beta.2` but `^1.2.0` does not admit `1.3.0-rc.1`.
+//!
+//! Anything that is not a range (dist-tags, git and tarball URLs, `file:`,
+//! `workspace:` ...) fails to parse rather than matching nothing.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Prerelease {
+    /// Numeric identifiers sort before alphanumeric ones.
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl fmt::Display for Prerelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prerelease::Numeric(n) => write!(f, "{}", n),
+            Prerelease::Alpha(s) => f.write_str(s),
+        }
+    }
+}
+
+/// A full `major.minor.patch[-pre][+build]` version. Build metadata is kept
+/// for display but ignored when comparing.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<Prerelease>,
+    pub build: Vec<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: Vec::new(),
+        }
+    }
+
+    /// Parses a full version; a leading `v` or `=` is accepted as npm does.
+    pub fn parse(text: &str) -> Option<Version> {
+        let partial = Partial::parse(text.trim().trim_start_matches(['v', '=']))?;
+        Some(Version {
+            major: partial.major?,
+            minor: partial.minor?,
+            patch: partial.patch?,
+            pre: partial.pre,
+            build: partial.build,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// The lowest version above every `major.minor.patch` prerelease, used as
+    /// an exclusive upper bound (`<2.0.0-0`).
+    fn floor(major: u64, minor: u64, patch: u64) -> Version {
+        Version {
+            pre: vec![Prerelease::Numeric(0)],
+            ..Version::new(major, minor, patch)
+        }
+    }
+
+    fn same_release(&self, other: &Version) -> bool {
+        (self.major, self.minor, self.patch) == (other.major, other.minor, other.patch)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                // A release sorts after all of its prereleases.
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        let mut sep = '-';
+        for id in &self.pre {
+            write!(f, "{}{}", sep, id)?;
+            sep = '.';
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl Op {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Lt => "<",
+            Op::Lte => "<=",
+            Op::Gt => ">",
+            Op::Gte => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparator {
+    pub op: Op,
+    pub version: Version,
+}
+
+impl Comparator {
+    fn new(op: Op, version: Version) -> Self {
+        Comparator { op, version }
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        let ord = version.cmp(&self.version);
+        match self.op {
+            Op::Eq => ord == Ordering::Equal,
+            Op::Lt => ord == Ordering::Less,
+            Op::Lte => ord != Ordering::Greater,
+            Op::Gt => ord == Ordering::Greater,
+            Op::Gte => ord != Ordering::Less,
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op.as_str(), self.version)
+    }
+}
+
+/// A parsed range: a version satisfies it when it satisfies every
+/// comparator of at least one set. An empty set matches any release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range {
+    pub sets: Vec<Vec<Comparator>>,
+}
+
+impl Range {
+    pub fn parse(text: &str) -> Option<Range> {
+        let sets = text
+            .split("||")
+            .map(parse_set)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Range { sets })
+    }
+
+    pub fn satisfies(&self, version: &Version) -> bool {
+        self.sets.iter().any(|set| {
+            set.iter().all(|c| c.matches(version))
+                && (!version.is_prerelease()
+                    || set
+                        .iter()
+                        .any(|c| c.version.is_prerelease() && c.version.same_release(version)))
+        })
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, set) in self.sets.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" || ")?;
+            }
+            if set.is_empty() {
+                f.write_str("*")?;
+            }
+            for (j, comparator) in set.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}", comparator)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `Some(true)` when `version` satisfies `range`, `None` when either is not
+/// semver.
+pub fn satisfies(version: &str, range: &str) -> Option<bool> {
+    Some(Range::parse(range)?.satisfies(&Version::parse(version)?))
+}
+
+/// A version with `x`, `X`, `*` or missing components. Every component is
+/// below `u64::MAX`, so desugaring can always bump it to an upper bound.
+#[derive(Debug)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<Prerelease>,
+    build: Vec<String>,
+}
+
+impl Partial {
+    fn parse(text: &str) -> Option<Partial> {
+        let (text, build) = match text.split_once('+') {
+            Some((head, build)) => (head, identifiers(build)?),
+            None => (text, Vec::new()),
+        };
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, identifiers(pre)?),
+            None => (text, Vec::new()),
+        };
+        let mut parts = core.split('.');
+        let mut next = || -> Option<Option<u64>> {
+            match parts.next() {
+                None => Some(None),
+                Some("x" | "X" | "*") => Some(None),
+                Some(n) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => {
+                    // `^18446744073709551615` has no upper bound to desugar to.
+                    let n: u64 = n.parse().ok()?;
+                    n.checked_add(1)?;
+                    Some(Some(n))
+                }
+                Some(_) => None,
+            }
+        };
+        let (major, minor, patch) = (next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        // Once a component is a wildcard the rest are too: `1.x.3` is `1.x`.
+        let minor = major.and(minor);
+        let patch = minor.and(patch);
+        if patch.is_none() && !pre.is_empty() {
+            return None;
+        }
+        let pre = pre
+            .into_iter()
+            .map(|id| match id.parse() {
+                Ok(n) if id.bytes().all(|b| b.is_ascii_digit()) => Prerelease::Numeric(n),
+                _ => Prerelease::Alpha(id),
+            })
+            .collect();
+        Some(Partial {
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        })
+    }
+
+    /// Missing components filled with zero.
+    fn lower(&self) -> Version {
+        Version {
+            major: self.major.unwrap_or(0),
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+            build: Vec::new(),
+        }
+    }
+
+    /// The exclusive bound just past every version the partial covers
+    /// (`1.2` → `<1.3.0-0`), or `None` for a full version or `*`.
+    fn upper(&self) -> Option<Version> {
+        match (self.major, self.minor, self.patch) {
+            (Some(major), None, _) => Some(Version::floor(major + 1, 0, 0)),
+            (Some(major), Some(minor), None) => Some(Version::floor(major, minor + 1, 0)),
+            _ => None,
+        }
+    }
+}
+
+fn identifiers(text: &str) -> Option<Vec<String>> {
+    let ids: Vec<String> = text.split('.').map(str::to_string).collect();
+    let valid = |id: &String| !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    ids.iter().all(valid).then_some(ids)
+}
+
+fn parse_set(text: &str) -> Option<Vec<Comparator>> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    if let [from, "-", to] = tokens.as_slice() {
+        return hyphen(from, to);
+    }
+    // `>= 1.2.3` is `>=1.2.3`: glue a bare operator to the version after it.
+    let mut set = Vec::new();
+    let mut pending: Option<&str> = None;
+    for token in tokens {
+        if let Some(op) = pending.take() {
+            set.extend(primitive(op, token)?);
+        } else if token.trim_end_matches(['<', '>', '=', '~', '^']).is_empty() {
+            pending = Some(token);
+        } else {
+            let split = token.find(|c: char| !matches!(c, '<' | '>' | '=' | '~' | '^')).unwrap_or(0);
+            set.extend(primitive(&token[..split], &token[split..])?);
+        }
+    }
+    if pending.is_some() {
+        return None;
+    }
+    Some(set)
+}
+
+/// `1.2 - 2.3` is `>=1.2.0 <2.4.0-0`.
+fn hyphen(from: &str, to: &str) -> Option<Vec<Comparator>> {
+    let (from, to) = (partial(from)?, partial(to)?);
+    let mut set = Vec::new();
+    if from.major.is_some() {
+        set.push(Comparator::new(Op::Gte, from.lower()));
+    }
+    if to.major.is_some() {
+        set.push(match to.upper() {
+            Some(bound) => Comparator::new(Op::Lt, bound),
+            None => Comparator::new(Op::Lte, to.lower()),
+        });
+    }
+    Some(set)
+}
+
+fn partial(text: &str) -> Option<Partial> {
+    Partial::parse(text.strip_prefix('v').unwrap_or(text))
+}
+
+/// Desugars one operator and (partial) version into primitives.
+fn primitive(op: &str, version: &str) -> Option<Vec<Comparator>> {
+    let v = partial(version)?;
+    let lower = v.lower();
+    let Some(major) = v.major else {
+        return match op {
+            // Nothing is below or above every version.
+            "<" | ">" => Some(vec![Comparator::new(Op::Lt, Version::floor(0, 0, 0))]),
+            "" | "=" | "<=" | ">=" | "~" | "~>" | "^" => Some(Vec::new()),
+            _ => None,
+        };
+    };
+    let set = match op {
+        "" | "=" => match v.upper() {
+            Some(upper) => vec![Comparator::new(Op::Gte, lower), Comparator::new(Op::Lt, upper)],
+            None => vec![Comparator::new(Op::Eq, lower)],
+        },
+        "~" | "~>" => {
+            let upper = match v.minor {
+                Some(minor) => Version::floor(major, minor + 1, 0),
+                None => Version::floor(major + 1, 0, 0),
+            };
+            vec![Comparator::new(Op::Gte, lower), Comparator::new(Op::Lt, upper)]
+        }
+        "^" => {
+            // Bump the first non-zero component, or the last one given.
+            let upper = match (v.minor, v.patch) {
+                _ if major > 0 => Version::floor(major + 1, 0, 0),
+                (None, _) => Version::floor(1, 0, 0),
+                (Some(minor), None) => Version::floor(0, minor + 1, 0),
+                (Some(minor), Some(_)) if minor > 0 => Version::floor(0, minor + 1, 0),
+                (Some(_), Some(patch)) => Version::floor(0, 0, patch + 1),
+            };
+            vec![Comparator::new(Op::Gte, lower), Comparator::new(Op::Lt, upper)]
+        }
+        ">" => match v.upper() {
+            // `>1.2` is `>=1.3.0`.
+            Some(upper) => vec![Comparator::new(Op::Gte, Version { pre: Vec::new(), ..upper })],
+            None => vec![Comparator::new(Op::Gt, lower)],
+        },
+        ">=" => vec![Comparator::new(Op::Gte, lower)],
+        "<" => match v.upper() {
+            Some(_) => vec![Comparator::new(Op::Lt, Version::floor(lower.major, lower.minor, 0))],
+            None => vec![Comparator::new(Op::Lt, lower)],
+        },
+        "<=" => match v.upper() {
+            Some(upper) => vec![Comparator::new(Op::Lt, upper)],
+            None => vec![Comparator::new(Op::Lte, lower)],
+        },
+        _ => return None,
+    };
+    Some(set)
+}
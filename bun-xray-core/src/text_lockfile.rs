@@ -0,0 +1,618 @@
+//! Decoder for Bun's text lockfile (`bun.lock`).
+//!
+//! The text format is JSON with comments and trailing commas. Packages are keyed
+//! by their install path (`"a/b"` means `node_modules/a/node_modules/b`) and each
+//! value is a tuple whose shape depends on the resolution:
+//!
+//! ```text
+//! npm:       ["name@1.2.3", "<registry or empty>", { info }, "sha512-..."]
+//! git/gh:    ["name@github:owner/repo#sha", { info }, "<bun tag>"]
+//! tarball:   ["name@https://host/pkg.tgz", { info }]
+//! workspace: ["name@workspace:packages/a"]
+//! ```
+//!
+//! Everything is mapped onto the same [`Lockfile`] model the binary parser
+//! produces, so the security checks do not care which format they were fed.
+//! Package id 0 is the root workspace, mirroring `bun.lockb`.
+
+use crate::hash::{name_hash, trusted_hash};
+use crate::model::{
+    ArchFlags, BehaviorFlags, BinSpec, CatalogGroup, DependencyEntry, DependencySpec, InstallTree,
+    InstalledPackage, LifecycleScripts, Lockfile, LockfileFormat, OsFlags, OverrideEntry, Package,
+    PackageMeta, PackageOrigin, PatchedEntry, ResolutionKind, TrailerInfo, TreeNode,
+    WorkspaceEntry,
+};
+use crate::parser::{PackageRef, ParseError, ParseWarning};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Dependency groups as they appear in workspace and package info objects.
+const DEPENDENCY_GROUPS: &[(&str, BehaviorFlags)] = &[
+    ("dependencies", BehaviorFlags::PROD),
+    ("devDependencies", BehaviorFlags::DEV),
+    ("optionalDependencies", BehaviorFlags::OPTIONAL),
+    ("peerDependencies", BehaviorFlags::PEER),
+];
+
+pub fn parse_text_lockfile(path: &Path) -> Result<Lockfile, ParseError> {
+    parse_text_lockfile_with_warnings(path).map(|(lf, _)| lf)
+}
+
+pub fn parse_text_lockfile_with_warnings(
+    path: &Path,
+) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let bytes = fs::read(path)?;
+    parse_text_bytes(&bytes)
+}
+
+pub(crate) fn parse_text_bytes(bytes: &[u8]) -> Result<(Lockfile, Vec<ParseWarning>), ParseError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|e| ParseError::Utf8.at(e.valid_up_to() as u64, "text"))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let root: Value = serde_json::from_str(&strip_jsonc(text))?;
+    let root = root
+        .as_object()
+        .ok_or_else(|| ParseError::TextLockfile("top-level value is not an object".into()))?;
+
+    let format_version = root
+        .get("lockfileVersion")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| ParseError::TextLockfile("missing lockfileVersion".into()))?
+        as u32;
+
+    let empty = Map::new();
+    let workspaces = root
+        .get("workspaces")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let package_entries = root
+        .get("packages")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut warnings = Vec::new();
+
+    // Root workspace occupies id 0, like in the binary format.
+    let root_ws = workspaces.get("").and_then(Value::as_object);
+    let mut packages = vec![Package {
+        name: root_ws
+            .and_then(|ws| ws.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        version: String::new(),
+        registry_url: ResolutionKind::Root.registry_url(),
+        integrity_hash: None,
+        resolution: ResolutionKind::Root,
+        dependencies: Vec::new(),
+        meta: PackageMeta::default(),
+        scripts: LifecycleScripts::default(),
+        bin: BinSpec::None,
+    }];
+    let mut keys: Vec<Vec<String>> = vec![Vec::new()];
+    let mut dep_sources: Vec<Option<&Map<String, Value>>> = vec![root_ws];
+
+    for (key, entry) in package_entries {
+        let Some(tuple) = entry.as_array() else {
+            warnings.push(ParseWarning::TextEntryNotArray { key: key.clone() });
+            continue;
+        };
+        match decode_entry(tuple) {
+            Some((mut pkg, info)) => {
+                pkg.meta.id = packages.len() as u32;
+                let source = match &pkg.resolution {
+                    ResolutionKind::Workspace { name: path } => {
+                        workspaces.get(path).and_then(Value::as_object)
+                    }
+                    _ => info,
+                };
+                keys.push(split_install_path(key));
+                dep_sources.push(source);
+                packages.push(pkg);
+            }
+            None => {
+                warnings.push(ParseWarning::TextEntryMalformed { key: key.clone() });
+            }
+        }
+    }
+
+    let ids: HashMap<&[String], u32> = keys
+        .iter()
+        .enumerate()
+        .map(|(id, key)| (key.as_slice(), id as u32))
+        .collect();
+
+    for (idx, source) in dep_sources.iter().enumerate() {
+        let Some(source) = source else { continue };
+        let deps = collect_dependencies(source, &keys[idx], &ids);
+        for dep in &deps {
+            let optional = dep
+                .behavior
+                .intersects(BehaviorFlags::OPTIONAL | BehaviorFlags::PEER);
+            if dep.resolved_package_id.is_none() && !optional {
+                warnings.push(ParseWarning::TextDependencyUnresolved {
+                    package: PackageRef {
+                        index: idx as u32,
+                        name: packages[idx].name.clone(),
+                    },
+                    dependency: dep.name.clone(),
+                });
+            }
+        }
+        packages[idx].dependencies = deps;
+    }
+
+    let mut trailers = decode_trailers(root, workspaces);
+    trailers.resolve_names(&packages);
+    let install_tree = build_install_tree(&keys);
+
+    Ok((
+        Lockfile {
+            format_version,
+            source_format: LockfileFormat::Text,
+            meta_hash: [0u8; 32],
+            packages,
+            trailers,
+            install_tree,
+            computed_meta_hash: None,
+            name_hash_mismatches: Vec::new(),
+            columns: Vec::new(),
+            coverage: Default::default(),
+            non_canonical: Vec::new(),
+        },
+        warnings,
+    ))
+}
+
+fn decode_entry(tuple: &[Value]) -> Option<(Package, Option<&Map<String, Value>>)> {
+    let ident = tuple.first()?.as_str()?;
+    let (name, spec) = split_ident(ident)?;
+    let resolution = classify_spec(spec);
+
+    let (registry, info, integrity) = match &resolution {
+        ResolutionKind::Npm { .. } => (
+            tuple.get(1).and_then(Value::as_str),
+            tuple.get(2).and_then(Value::as_object),
+            tuple.get(3).and_then(Value::as_str),
+        ),
+        _ => (None, tuple.iter().skip(1).find_map(Value::as_object), None),
+    };
+
+    let resolution = match resolution {
+        ResolutionKind::Npm { version, .. } => ResolutionKind::Npm {
+            version,
+            registry: match registry {
+                Some(r) if !r.is_empty() => r.to_string(),
+                _ => DEFAULT_REGISTRY.to_string(),
+            },
+        },
+        // git and github entries end with the commit Bun resolved.
+        ResolutionKind::Git { owner, repo, commit, .. } => ResolutionKind::Git {
+            owner,
+            repo,
+            commit,
+            resolved: resolved_commit(tuple),
+        },
+        ResolutionKind::Github {
+            owner,
+            repo,
+            reference,
+            ..
+        } => ResolutionKind::Github {
+            owner,
+            repo,
+            reference,
+            resolved: resolved_commit(tuple),
+        },
+        other => other,
+    };
+
+    let meta = PackageMeta {
+        origin: match &resolution {
+            ResolutionKind::Npm { .. } => PackageOrigin::Npm,
+            ResolutionKind::LocalTarball { .. } | ResolutionKind::RemoteTarball { .. } => {
+                PackageOrigin::Tarball
+            }
+            _ => PackageOrigin::Local,
+        },
+        os: OsFlags::from_npm_list(info.map(|i| string_list(i, "os")).unwrap_or_default()),
+        arch: ArchFlags::from_npm_list(info.map(|i| string_list(i, "cpu")).unwrap_or_default()),
+        // assigned by the caller once the package's position is known
+        id: 0,
+        // bun.lock does not record whether a package has install scripts
+        has_install_script: None,
+    };
+
+    let pkg = Package {
+        name: name.to_string(),
+        version: resolution.version(),
+        registry_url: resolution.registry_url(),
+        integrity_hash: integrity.filter(|s| !s.is_empty()).map(str::to_string),
+        resolution,
+        dependencies: Vec::new(),
+        meta,
+        // bun.lock does not carry lifecycle scripts
+        scripts: LifecycleScripts::default(),
+        bin: info.map(decode_bin).unwrap_or_default(),
+    };
+    Some((pkg, info))
+}
+
+fn resolved_commit(tuple: &[Value]) -> String {
+    tuple.iter().skip(1).find_map(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn decode_bin(info: &Map<String, Value>) -> BinSpec {
+    if let Some(bins) = info.get("bin").and_then(Value::as_object) {
+        return BinSpec::Map {
+            entries: bins
+                .iter()
+                .map(|(name, path)| (name.clone(), path.as_str().unwrap_or_default().to_string()))
+                .collect(),
+        };
+    }
+    if let Some(path) = info.get("bin").and_then(Value::as_str) {
+        return BinSpec::File {
+            path: path.to_string(),
+        };
+    }
+    match info.get("binDir").and_then(Value::as_str) {
+        Some(dir) => BinSpec::Dir {
+            path: dir.to_string(),
+        },
+        None => BinSpec::None,
+    }
+}
+
+/// Reads a field that npm allows as either a string or a list of strings.
+fn string_list<'a>(info: &'a Map<String, Value>, field: &str) -> Vec<&'a str> {
+    match info.get(field) {
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Splits `name@spec`, keeping the leading `@` of scoped names.
+fn split_ident(ident: &str) -> Option<(&str, &str)> {
+    let at = ident.get(1..)?.find('@')? + 1;
+    Some((&ident[..at], &ident[at + 1..]))
+}
+
+fn classify_spec(spec: &str) -> ResolutionKind {
+    if spec.starts_with("root:") {
+        ResolutionKind::Root
+    } else if let Some(path) = spec.strip_prefix("workspace:") {
+        ResolutionKind::Workspace {
+            name: path.to_string(),
+        }
+    } else if let Some(path) = spec.strip_prefix("link:") {
+        ResolutionKind::Symlink {
+            path: path.to_string(),
+        }
+    } else if let Some(path) = spec.strip_prefix("file:") {
+        if path.ends_with(".tgz") || path.ends_with(".tar.gz") {
+            ResolutionKind::LocalTarball {
+                path: path.to_string(),
+            }
+        } else {
+            ResolutionKind::Folder {
+                path: path.to_string(),
+            }
+        }
+    } else if let Some(url) = spec.strip_prefix("module:") {
+        ResolutionKind::SingleFileModule {
+            url: url.to_string(),
+        }
+    } else if let Some(rest) = spec.strip_prefix("github:") {
+        let (repo_path, reference) = rest.split_once('#').unwrap_or((rest, ""));
+        let (owner, repo) = repo_path.split_once('/').unwrap_or(("", repo_path));
+        ResolutionKind::Github {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            reference: reference.to_string(),
+            resolved: String::new(),
+        }
+    } else if spec.starts_with("git+") || spec.starts_with("git:") {
+        // Bun stores the URL without the `git+` label it prints.
+        let url = spec.strip_prefix("git+").unwrap_or(spec);
+        let (repo, commit) = url.split_once('#').unwrap_or((url, ""));
+        ResolutionKind::Git {
+            owner: String::new(),
+            repo: repo.to_string(),
+            commit: commit.to_string(),
+            resolved: String::new(),
+        }
+    } else if spec.starts_with("http://") || spec.starts_with("https://") {
+        ResolutionKind::RemoteTarball {
+            url: spec.to_string(),
+        }
+    } else {
+        ResolutionKind::Npm {
+            version: spec.to_string(),
+            registry: String::new(),
+        }
+    }
+}
+
+/// Splits a `packages` key into node_modules segments, keeping `@scope/name` together.
+fn split_install_path(key: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut pending_scope: Option<&str> = None;
+    for part in key.split('/') {
+        if let Some(scope) = pending_scope.take() {
+            out.push(format!("{scope}/{part}"));
+        } else if part.starts_with('@') {
+            pending_scope = Some(part);
+        } else {
+            out.push(part.to_string());
+        }
+    }
+    if let Some(scope) = pending_scope {
+        out.push(scope.to_string());
+    }
+    out
+}
+
+fn collect_dependencies(
+    source: &Map<String, Value>,
+    key: &[String],
+    ids: &HashMap<&[String], u32>,
+) -> Vec<DependencyEntry> {
+    let optional_peers: Vec<&str> = source
+        .get("optionalPeers")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut out = Vec::new();
+    for (group, flag) in DEPENDENCY_GROUPS {
+        let Some(deps) = source.get(*group).and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, req) in deps {
+            let req = req.as_str().unwrap_or_default().to_string();
+            let mut behavior = *flag;
+            if *flag == BehaviorFlags::PEER && optional_peers.contains(&name.as_str()) {
+                behavior |= BehaviorFlags::OPTIONAL;
+            }
+            if req.starts_with("workspace:") {
+                behavior |= BehaviorFlags::WORKSPACE;
+            }
+            out.push(DependencyEntry {
+                name: name.clone(),
+                spec: DependencySpec::infer(&req),
+                req,
+                behavior,
+                resolved_package_id: resolve_from(key, name, ids),
+            });
+        }
+    }
+    out
+}
+
+/// Node-style lookup: the closest `node_modules/<name>` walking up from `key`.
+fn resolve_from(key: &[String], name: &str, ids: &HashMap<&[String], u32>) -> Option<u32> {
+    let mut candidate: Vec<String> = key.to_vec();
+    loop {
+        candidate.push(name.to_string());
+        if let Some(id) = ids.get(candidate.as_slice()) {
+            return Some(*id);
+        }
+        candidate.pop();
+        candidate.pop()?;
+    }
+}
+
+/// One tree node per `node_modules` directory implied by the package keys.
+fn build_install_tree(keys: &[Vec<String>]) -> InstallTree {
+    let mut tree = InstallTree::default();
+    let mut node_ids: HashMap<Vec<String>, u32> = HashMap::new();
+    // Package id 0 is the root workspace, which is not installed anywhere.
+    for (id, key) in keys.iter().enumerate().skip(1) {
+        let Some((name, parent_key)) = key.split_last() else {
+            continue;
+        };
+        let node = ensure_node(&mut tree, &mut node_ids, parent_key);
+        let node = &mut tree.nodes[node as usize];
+        node.entries.push(InstalledPackage {
+            name: name.clone(),
+            path: format!("{}/{}", node.path, name),
+            dependency_id: None,
+            package_id: Some(id as u32),
+        });
+    }
+    tree
+}
+
+fn ensure_node(
+    tree: &mut InstallTree,
+    node_ids: &mut HashMap<Vec<String>, u32>,
+    key: &[String],
+) -> u32 {
+    if let Some(id) = node_ids.get(key) {
+        return *id;
+    }
+    let (parent, path) = match key.split_last() {
+        None => (None, String::from("node_modules")),
+        Some((name, parent_key)) => {
+            let parent = ensure_node(tree, node_ids, parent_key);
+            let path = format!("{}/{}/node_modules", tree.nodes[parent as usize].path, name);
+            (Some(parent), path)
+        }
+    };
+    let id = tree.nodes.len() as u32;
+    tree.nodes.push(TreeNode {
+        id,
+        parent,
+        dependency_id: None,
+        path,
+        entries: Vec::new(),
+    });
+    node_ids.insert(key.to_vec(), id);
+    id
+}
+
+fn decode_trailers(root: &Map<String, Value>, workspaces: &Map<String, Value>) -> TrailerInfo {
+    let mut info = TrailerInfo::default();
+
+    if let Some(trusted) = root.get("trustedDependencies").and_then(Value::as_array) {
+        info.trusted_hashes = trusted
+            .iter()
+            .filter_map(Value::as_str)
+            .map(trusted_hash)
+            .collect();
+        info.has_empty_trusted = info.trusted_hashes.is_empty();
+    }
+
+    if let Some(overrides) = root.get("overrides").and_then(Value::as_object) {
+        info.overrides = overrides
+            .iter()
+            .map(|(name, req)| OverrideEntry {
+                name_hash: name_hash(name),
+                name: None,
+                dependency: spec_entry(name, req),
+            })
+            .collect();
+    }
+
+    if let Some(patched) = root.get("patchedDependencies").and_then(Value::as_object) {
+        info.patched = patched
+            .iter()
+            .map(|(name_version, path)| PatchedEntry {
+                name_version_hash: name_hash(name_version),
+                name_version: None,
+                path: path.as_str().unwrap_or_default().to_string(),
+                patch_hash: None,
+            })
+            .collect();
+    }
+
+    if let Some(catalog) = root.get("catalog").and_then(Value::as_object) {
+        info.default_catalog = catalog
+            .iter()
+            .map(|(name, req)| spec_entry(name, req))
+            .collect();
+    }
+
+    if let Some(catalogs) = root.get("catalogs").and_then(Value::as_object) {
+        info.catalogs = catalogs
+            .iter()
+            .map(|(group, deps)| CatalogGroup {
+                name: group.clone(),
+                dependencies: deps
+                    .as_object()
+                    .map(|deps| deps.iter().map(|(n, r)| spec_entry(n, r)).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+    }
+
+    info.workspaces = workspaces
+        .iter()
+        .filter(|(path, _)| !path.is_empty())
+        .map(|(path, ws)| {
+            let field = |key: &str| ws.get(key).and_then(Value::as_str).map(str::to_string);
+            let name = field("name");
+            WorkspaceEntry {
+                name_hash: name_hash(name.as_deref().unwrap_or_default()),
+                name,
+                version: field("version"),
+                path: Some(path.clone()),
+            }
+        })
+        .collect();
+    info.workspaces_count = info.workspaces.len();
+    info
+}
+
+fn spec_entry(name: &str, req: &Value) -> DependencyEntry {
+    let req = req.as_str().unwrap_or_default().to_string();
+    DependencyEntry {
+        name: name.to_string(),
+        spec: DependencySpec::infer(&req),
+        req,
+        behavior: BehaviorFlags::empty(),
+        resolved_package_id: None,
+    }
+}
+
+/// Removes `//` and `/* */` comments and trailing commas so serde_json accepts the input.
+fn strip_jsonc(input: &str) -> String {
+    let mut without_comments = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            without_comments.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        without_comments.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                without_comments.push(c);
+            }
+            ('/', Some('/')) => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        without_comments.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for skipped in chars.by_ref() {
+                    if prev == '*' && skipped == '/' {
+                        break;
+                    }
+                    prev = skipped;
+                }
+                without_comments.push(' ');
+            }
+            _ => without_comments.push(c),
+        }
+    }
+
+    let mut out = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let bytes: Vec<char> = without_comments.chars().collect();
+    for (i, &c) in bytes.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            out.push(c);
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = bytes[i + 1..].iter().find(|n| !n.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
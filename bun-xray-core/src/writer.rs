@@ -0,0 +1,657 @@
+//! Encoder for binary `bun.lockb` files; the inverse of [`crate::parser`].
+//!
+//! The layout mirrors what the parser reads: header, package columns, the
+//! buffer pointer block followed by the buffers, a zero sentinel and the
+//! tagged trailers. Anything the model does not keep (`man_dir`, the config
+//! version trailer) is written as zero; unknown trailer sections are dropped.
+//! Older format revisions are written in the current layout.
+
+use crate::hash::{self, name_hash};
+use crate::model::{
+    BinSpec, DependencyEntry, LifecycleScripts, Lockfile, Package, PackageColumn,
+    ResolutionKind,
+};
+use crate::parser::{
+    Bin, BufferKind, DependencyExternal, ExternalSlice, ExternalString, Integrity, Meta,
+    PackageTableHeader, PatchedDepExternal, Repository, Resolution, ResolutionTag,
+    ResolutionValue, Scripts, SemverString, SemverVersion, SemverVersionTag, TreeExternal,
+    VersionedUrl, BUFFER_KINDS, CURRENT_FORMAT, INVALID_ID, MAGIC,
+};
+use base64::alphabet::STANDARD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use binrw::{BinWrite, BinWriterExt};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WriteError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("binary encoding error: {0}")]
+    Binrw(#[from] binrw::Error),
+    #[error("cannot encode version {0:?}")]
+    InvalidVersion(String),
+    #[error("cannot encode integrity {0:?}")]
+    InvalidIntegrity(String),
+    #[error("lockfile too large to encode")]
+    TooLarge,
+}
+
+/// Encodes `lockfile` as a v3 `bun.lockb`. Text lockfiles can be written too;
+/// they get the current format version and a freshly computed meta hash.
+pub fn write_lockfile(lockfile: &Lockfile) -> Result<Vec<u8>, WriteError> {
+    Encoder::default().encode(lockfile)
+}
+
+pub fn write_lockfile_to_path(lockfile: &Lockfile, path: &Path) -> Result<(), WriteError> {
+    fs::write(path, write_lockfile(lockfile)?)?;
+    Ok(())
+}
+
+/// Interned `string_bytes`; each distinct string is stored once.
+#[derive(Default)]
+struct StringTable {
+    bytes: Vec<u8>,
+    offsets: HashMap<String, u32>,
+}
+
+impl StringTable {
+    fn add(&mut self, s: &str) -> Result<SemverString, WriteError> {
+        let raw = s.as_bytes();
+        // Inline strings end at the first NUL and must leave the pointer bit clear.
+        let inline = raw.len() <= 8
+            && !raw.contains(&0)
+            && (raw.len() < 8 || raw[7] & 0x80 == 0);
+        if inline {
+            let mut bytes = [0u8; 8];
+            bytes[..raw.len()].copy_from_slice(raw);
+            return Ok(SemverString { bytes });
+        }
+        let off = match self.offsets.get(s) {
+            Some(off) => *off,
+            None => {
+                let off = u32::try_from(self.bytes.len()).map_err(|_| WriteError::TooLarge)?;
+                self.bytes.extend_from_slice(raw);
+                self.offsets.insert(s.to_string(), off);
+                off
+            }
+        };
+        let len = u32::try_from(raw.len())
+            .ok()
+            .filter(|len| *len < 1 << 31)
+            .ok_or(WriteError::TooLarge)?;
+        let packed = u64::from(off) | (u64::from(len) << 32) | (1u64 << 63);
+        Ok(SemverString {
+            bytes: packed.to_le_bytes(),
+        })
+    }
+
+    fn add_external(&mut self, s: &str) -> Result<ExternalString, WriteError> {
+        Ok(ExternalString {
+            value: self.add(s)?,
+            hash: name_hash(s),
+        })
+    }
+
+    fn add_version(&mut self, version: &str) -> Result<SemverVersion, WriteError> {
+        let invalid = || WriteError::InvalidVersion(version.to_string());
+        let (rest, build) = match version.split_once('+') {
+            Some((rest, build)) => (rest, build),
+            None => (version, ""),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, pre),
+            None => (rest, ""),
+        };
+        let mut parts = core.split('.');
+        let mut number = || -> Result<u64, WriteError> {
+            parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)
+        };
+        let (major, minor, patch) = (number()?, number()?, number()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(SemverVersion {
+            major,
+            minor,
+            patch,
+            tag: SemverVersionTag {
+                pre: self.add_external(pre)?,
+                build: self.add_external(build)?,
+            },
+        })
+    }
+}
+
+#[derive(Default)]
+struct Encoder {
+    strings: StringTable,
+    dependencies: Vec<DependencyExternal>,
+    resolutions: Vec<u32>,
+    extern_strings: Vec<ExternalString>,
+    trees: Vec<TreeExternal>,
+    hoisted: Vec<u32>,
+}
+
+struct Columns {
+    names: Vec<SemverString>,
+    name_hashes: Vec<u64>,
+    resolutions: Vec<Resolution>,
+    dep_slices: Vec<ExternalSlice>,
+    res_slices: Vec<ExternalSlice>,
+    metas: Vec<Meta>,
+    bins: Vec<Bin>,
+    scripts: Vec<Scripts>,
+}
+
+impl Encoder {
+    fn encode(mut self, lockfile: &Lockfile) -> Result<Vec<u8>, WriteError> {
+        let columns = self.encode_packages(&lockfile.packages)?;
+        self.encode_install_tree(lockfile)?;
+        // Trailers reference string bytes, so encode them before laying out buffers.
+        let trailers = self.encode_trailers(lockfile)?;
+
+        // Always recomputed: the caller may have edited the packages since
+        // the stored hash was read.
+        let meta_hash = hash::meta_hash(&lockfile.packages);
+
+        let mut out = Cursor::new(Vec::new());
+        out.get_mut().extend_from_slice(MAGIC);
+        out.set_position(MAGIC.len() as u64);
+        out.write_le(&CURRENT_FORMAT)?;
+        out.write_le(&meta_hash)?;
+        let total_size_pos = out.position();
+        out.write_le(&0u64)?;
+
+        let header_pos = out.position();
+        let header_len = 5 * 8;
+        let begin = header_pos + header_len;
+        out.set_position(begin);
+        write_all(&mut out, &columns.names)?;
+        write_all(&mut out, &columns.name_hashes)?;
+        write_all(&mut out, &columns.resolutions)?;
+        write_all(&mut out, &columns.dep_slices)?;
+        write_all(&mut out, &columns.res_slices)?;
+        write_all(&mut out, &columns.metas)?;
+        write_all(&mut out, &columns.bins)?;
+        // Keep a binary source's column set; its scripts are all empty when
+        // it had no scripts column.
+        let with_scripts =
+            lockfile.columns.is_empty() || lockfile.columns.contains(&PackageColumn::Scripts);
+        if with_scripts {
+            write_all(&mut out, &columns.scripts)?;
+        }
+        let end = out.position();
+        out.set_position(header_pos);
+        out.write_le(&PackageTableHeader {
+            len: lockfile.packages.len() as u64,
+            alignment: 8,
+            field_count: if with_scripts { 8 } else { 7 },
+            begin,
+            end,
+        })?;
+        out.set_position(end);
+
+        let end_pos = self.write_buffers(&mut out)?;
+        out.set_position(end_pos);
+        out.write_le(&0u64)?;
+        for part in trailers {
+            match part {
+                TrailerPart::Tag(tag) => out.get_mut().extend_from_slice(&tag),
+                TrailerPart::Array(data) => {
+                    let begin = out.get_ref().len() as u64 + 16;
+                    out.get_mut().extend_from_slice(&begin.to_le_bytes());
+                    out.get_mut()
+                        .extend_from_slice(&(begin + data.len() as u64).to_le_bytes());
+                    out.get_mut().extend_from_slice(&data);
+                }
+            }
+        }
+
+        let total_size = out.get_ref().len() as u64;
+        out.set_position(total_size_pos);
+        out.write_le(&total_size)?;
+        Ok(out.into_inner())
+    }
+
+    fn encode_packages(&mut self, packages: &[Package]) -> Result<Columns, WriteError> {
+        let mut columns = Columns {
+            names: Vec::with_capacity(packages.len()),
+            name_hashes: Vec::with_capacity(packages.len()),
+            resolutions: Vec::with_capacity(packages.len()),
+            dep_slices: Vec::with_capacity(packages.len()),
+            res_slices: Vec::with_capacity(packages.len()),
+            metas: Vec::with_capacity(packages.len()),
+            bins: Vec::with_capacity(packages.len()),
+            scripts: Vec::with_capacity(packages.len()),
+        };
+        for pkg in packages {
+            columns.names.push(self.strings.add(&pkg.name)?);
+            columns.name_hashes.push(name_hash(&pkg.name));
+            columns.resolutions.push(self.encode_resolution(&pkg.resolution)?);
+
+            // Dependencies and their resolved ids share one slice.
+            let slice = ExternalSlice {
+                off: self.dependencies.len() as u32,
+                len: pkg.dependencies.len() as u32,
+            };
+            for dep in &pkg.dependencies {
+                let record = self.encode_dependency(dep)?;
+                self.dependencies.push(record);
+                self.resolutions
+                    .push(dep.resolved_package_id.unwrap_or(INVALID_ID));
+            }
+            columns.dep_slices.push(slice);
+            columns.res_slices.push(slice);
+
+            columns.metas.push(Meta {
+                origin: u8::from(pkg.meta.origin),
+                _padding_origin: 0,
+                arch: pkg.meta.arch.bits(),
+                os: pkg.meta.os.bits(),
+                _padding_os: 0,
+                id: pkg.meta.id,
+                man_dir: SemverString { bytes: [0; 8] },
+                integrity: encode_integrity(pkg.integrity_hash.as_deref())?,
+                has_install_script: match pkg.meta.has_install_script {
+                    None => 0,
+                    Some(false) => 1,
+                    Some(true) => 2,
+                },
+                _padding_integrity: [0; 2],
+            });
+            columns.bins.push(self.encode_bin(&pkg.bin)?);
+            columns.scripts.push(self.encode_scripts(&pkg.scripts)?);
+        }
+        Ok(columns)
+    }
+
+    fn encode_resolution(&mut self, resolution: &ResolutionKind) -> Result<Resolution, WriteError> {
+        let (tag, value) = match resolution {
+            ResolutionKind::Root => (ResolutionTag::Root, ResolutionValue::Root),
+            ResolutionKind::Npm { version, registry } => (
+                ResolutionTag::Npm,
+                ResolutionValue::Npm(VersionedUrl {
+                    url: self.strings.add(registry)?,
+                    version: self.strings.add_version(version)?,
+                }),
+            ),
+            ResolutionKind::Git {
+                owner,
+                repo,
+                commit,
+                resolved,
+            } => (
+                ResolutionTag::Git,
+                ResolutionValue::Git(self.encode_repository(owner, repo, commit, resolved)?),
+            ),
+            ResolutionKind::Github {
+                owner,
+                repo,
+                reference,
+                resolved,
+            } => (
+                ResolutionTag::Github,
+                ResolutionValue::Github(self.encode_repository(owner, repo, reference, resolved)?),
+            ),
+            ResolutionKind::Folder { path } => {
+                (ResolutionTag::Folder, ResolutionValue::Folder(self.strings.add(path)?))
+            }
+            ResolutionKind::LocalTarball { path } => (
+                ResolutionTag::LocalTarball,
+                ResolutionValue::LocalTarball(self.strings.add(path)?),
+            ),
+            ResolutionKind::RemoteTarball { url } => (
+                ResolutionTag::RemoteTarball,
+                ResolutionValue::RemoteTarball(self.strings.add(url)?),
+            ),
+            ResolutionKind::Symlink { path } => {
+                (ResolutionTag::Symlink, ResolutionValue::Symlink(self.strings.add(path)?))
+            }
+            ResolutionKind::Workspace { name } => (
+                ResolutionTag::Workspace,
+                ResolutionValue::Workspace(self.strings.add(name)?),
+            ),
+            ResolutionKind::SingleFileModule { url } => (
+                ResolutionTag::SingleFileModule,
+                ResolutionValue::SingleFileModule(self.strings.add(url)?),
+            ),
+            ResolutionKind::Unknown(_) => {
+                (ResolutionTag::Uninitialized, ResolutionValue::Uninitialized)
+            }
+        };
+        Ok(Resolution {
+            tag,
+            _padding: [0; 7],
+            value,
+        })
+    }
+
+    fn encode_repository(
+        &mut self,
+        owner: &str,
+        repo: &str,
+        committish: &str,
+        resolved: &str,
+    ) -> Result<Repository, WriteError> {
+        Ok(Repository {
+            owner: self.strings.add(owner)?,
+            repo: self.strings.add(repo)?,
+            committish: self.strings.add(committish)?,
+            resolved: self.strings.add(resolved)?,
+            package_name: self.strings.add("")?,
+        })
+    }
+
+    fn encode_dependency(&mut self, dep: &DependencyEntry) -> Result<DependencyExternal, WriteError> {
+        Ok(DependencyExternal {
+            name: self.strings.add(&dep.name)?,
+            name_hash: name_hash(&dep.name),
+            behavior: dep.behavior.bits(),
+            version_tag: dep.spec.tag(),
+            version_literal: self.strings.add(&dep.req)?,
+        })
+    }
+
+    fn encode_bin(&mut self, bin: &BinSpec) -> Result<Bin, WriteError> {
+        let mut value = [0u8; 16];
+        let tag = match bin {
+            BinSpec::None => 0,
+            BinSpec::File { path } => {
+                value[..8].copy_from_slice(&self.strings.add(path)?.bytes);
+                1
+            }
+            BinSpec::NamedFile { name, path } => {
+                value[..8].copy_from_slice(&self.strings.add(name)?.bytes);
+                value[8..].copy_from_slice(&self.strings.add(path)?.bytes);
+                2
+            }
+            BinSpec::Dir { path } => {
+                value[..8].copy_from_slice(&self.strings.add(path)?.bytes);
+                3
+            }
+            BinSpec::Map { entries } => {
+                let off = self.extern_strings.len() as u32;
+                for (name, path) in entries {
+                    let name = self.strings.add_external(name)?;
+                    let path = self.strings.add_external(path)?;
+                    self.extern_strings.extend([name, path]);
+                }
+                value[..4].copy_from_slice(&off.to_le_bytes());
+                value[4..8].copy_from_slice(&((entries.len() * 2) as u32).to_le_bytes());
+                4
+            }
+            BinSpec::Unknown(tag) => *tag,
+        };
+        Ok(Bin {
+            tag,
+            _pad: [0; 3],
+            value,
+        })
+    }
+
+    fn encode_scripts(&mut self, scripts: &LifecycleScripts) -> Result<Scripts, WriteError> {
+        let mut add = |s: &Option<String>| self.strings.add(s.as_deref().unwrap_or_default());
+        Ok(Scripts {
+            preinstall: add(&scripts.preinstall)?,
+            install: add(&scripts.install)?,
+            postinstall: add(&scripts.postinstall)?,
+            preprepare: add(&scripts.preprepare)?,
+            prepare: add(&scripts.prepare)?,
+            postprepare: add(&scripts.postprepare)?,
+            filled: 1,
+            _pad: [0; 7],
+        })
+    }
+
+    /// Trees and hoisted ids. Text lockfiles carry no dependency ids, so those
+    /// are looked up by `(name, package id)` among the encoded dependencies.
+    fn encode_install_tree(&mut self, lockfile: &Lockfile) -> Result<(), WriteError> {
+        let mut index = 0u32;
+        let mut by_edge: HashMap<(&str, u32), u32> = HashMap::new();
+        for pkg in &lockfile.packages {
+            for dep in &pkg.dependencies {
+                if let Some(package_id) = dep.resolved_package_id {
+                    by_edge.entry((dep.name.as_str(), package_id)).or_insert(index);
+                }
+                index += 1;
+            }
+        }
+
+        let nodes = &lockfile.install_tree.nodes;
+        let positions: HashMap<u32, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(pos, node)| (node.id, pos as u32))
+            .collect();
+        let mut entry_ids: HashMap<&str, u32> = HashMap::new();
+        for (pos, node) in nodes.iter().enumerate() {
+            let off = self.hoisted.len() as u32;
+            for entry in &node.entries {
+                let dep_id = entry.dependency_id.or_else(|| {
+                    entry
+                        .package_id
+                        .and_then(|id| by_edge.get(&(entry.name.as_str(), id)).copied())
+                });
+                let Some(dep_id) = dep_id else { continue };
+                self.hoisted.push(dep_id);
+                entry_ids.insert(entry.path.as_str(), dep_id);
+            }
+            let dependency_id = node.dependency_id.or_else(|| {
+                let dir = node.path.strip_suffix("/node_modules")?;
+                entry_ids.get(dir).copied()
+            });
+            self.trees.push(TreeExternal {
+                id: pos as u32,
+                dependency_id: dependency_id.unwrap_or(INVALID_ID),
+                parent: node
+                    .parent
+                    .and_then(|p| positions.get(&p).copied())
+                    .unwrap_or(INVALID_ID),
+                dependencies: ExternalSlice {
+                    off,
+                    len: self.hoisted.len() as u32 - off,
+                },
+            });
+        }
+        Ok(())
+    }
+
+    fn encode_trailers(&mut self, lockfile: &Lockfile) -> Result<Vec<TrailerPart>, WriteError> {
+        let trailers = &lockfile.trailers;
+        let mut out = Vec::new();
+
+        if !trailers.workspaces.is_empty() {
+            let mut version_hashes = Vec::new();
+            let mut versions = Vec::new();
+            let mut path_hashes = Vec::new();
+            let mut paths = Vec::new();
+            for ws in &trailers.workspaces {
+                if let Some(version) = &ws.version {
+                    version_hashes.push(ws.name_hash);
+                    versions.push(self.strings.add_version(version)?);
+                }
+                if let Some(path) = &ws.path {
+                    path_hashes.push(ws.name_hash);
+                    paths.push(self.strings.add(path)?);
+                }
+            }
+            out.push(TrailerPart::Tag(*b"wOrKsPaC"));
+            write_array(&mut out, &version_hashes)?;
+            write_array(&mut out, &versions)?;
+            write_array(&mut out, &path_hashes)?;
+            write_array(&mut out, &paths)?;
+        }
+
+        if !trailers.trusted_hashes.is_empty() {
+            out.push(TrailerPart::Tag(*b"tRuStEDd"));
+            write_array(&mut out, &trailers.trusted_hashes)?;
+        }
+        if trailers.has_empty_trusted {
+            out.push(TrailerPart::Tag(*b"eMpTrUsT"));
+        }
+
+        if !trailers.overrides.is_empty() {
+            let hashes: Vec<u64> = trailers.overrides.iter().map(|o| o.name_hash).collect();
+            let deps = trailers
+                .overrides
+                .iter()
+                .map(|o| self.encode_dependency(&o.dependency))
+                .collect::<Result<Vec<_>, _>>()?;
+            out.push(TrailerPart::Tag(*b"oVeRriDs"));
+            write_array(&mut out, &hashes)?;
+            write_array(&mut out, &deps)?;
+        }
+
+        if !trailers.patched.is_empty() {
+            let hashes: Vec<u64> = trailers.patched.iter().map(|p| p.name_version_hash).collect();
+            let mut records = Vec::with_capacity(trailers.patched.len());
+            for patch in &trailers.patched {
+                records.push(PatchedDepExternal {
+                    path: self.strings.add(&patch.path)?,
+                    _padding: [0; 7],
+                    patchfile_hash_is_null: u8::from(patch.patch_hash.is_none()),
+                    patch_hash: patch.patch_hash.unwrap_or_default(),
+                });
+            }
+            out.push(TrailerPart::Tag(*b"pAtChEdD"));
+            write_array(&mut out, &hashes)?;
+            write_array(&mut out, &records)?;
+        }
+
+        if !trailers.default_catalog.is_empty() || !trailers.catalogs.is_empty() {
+            out.push(TrailerPart::Tag(*b"cAtAlOgS"));
+            self.write_catalog(&mut out, &trailers.default_catalog)?;
+            let names = trailers
+                .catalogs
+                .iter()
+                .map(|group| self.strings.add(&group.name))
+                .collect::<Result<Vec<_>, _>>()?;
+            write_array(&mut out, &names)?;
+            for group in &trailers.catalogs {
+                self.write_catalog(&mut out, &group.dependencies)?;
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn write_catalog(
+        &mut self,
+        out: &mut Vec<TrailerPart>,
+        deps: &[DependencyEntry],
+    ) -> Result<(), WriteError> {
+        let names = deps
+            .iter()
+            .map(|d| self.strings.add(&d.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let records = deps
+            .iter()
+            .map(|d| self.encode_dependency(d))
+            .collect::<Result<Vec<_>, _>>()?;
+        write_array(out, &names)?;
+        write_array(out, &records)
+    }
+
+    /// Writes the pointer block at the cursor, then each buffer aligned after
+    /// it. Returns where the sentinel goes.
+    fn write_buffers(&self, out: &mut Cursor<Vec<u8>>) -> Result<u64, WriteError> {
+        let mut kinds: Vec<BufferKind> = BUFFER_KINDS.to_vec();
+        kinds.sort_by_key(|k| std::cmp::Reverse(k.alignment()));
+
+        let block_pos = out.position();
+        let mut pos = block_pos + (kinds.len() * 16) as u64;
+        let mut locations = Vec::with_capacity(kinds.len());
+        for kind in &kinds {
+            let data = match kind {
+                BufferKind::Dependencies => to_bytes(&self.dependencies)?,
+                BufferKind::ExternStrings => to_bytes(&self.extern_strings)?,
+                BufferKind::Trees => to_bytes(&self.trees)?,
+                BufferKind::HoistedDependencies => to_bytes(&self.hoisted)?,
+                BufferKind::Resolutions => to_bytes(&self.resolutions)?,
+                BufferKind::StringBytes => self.strings.bytes.clone(),
+            };
+            pos = pos.next_multiple_of(kind.alignment() as u64);
+            out.set_position(pos);
+            out.get_mut().resize(pos as usize, 0);
+            out.get_mut().extend_from_slice(&data);
+            locations.push((pos, pos + data.len() as u64));
+            pos += data.len() as u64;
+        }
+
+        out.set_position(block_pos);
+        for (begin, end) in locations {
+            out.write_le(&begin)?;
+            out.write_le(&end)?;
+        }
+        Ok(pos)
+    }
+}
+
+fn encode_integrity(integrity: Option<&str>) -> Result<Integrity, WriteError> {
+    let mut value = [0u8; 64];
+    let Some(integrity) = integrity else {
+        return Ok(Integrity { tag: 0, value });
+    };
+    let invalid = || WriteError::InvalidIntegrity(integrity.to_string());
+    let (algo, digest) = integrity.split_once('-').ok_or_else(invalid)?;
+    let (tag, len) = match algo {
+        "sha1" => (1, 20),
+        "sha256" => (2, 32),
+        "sha384" => (3, 48),
+        "sha512" => (4, 64),
+        _ => return Err(invalid()),
+    };
+    // Lockfiles in the wild are not always canonical base64.
+    let engine = GeneralPurpose::new(
+        &STANDARD,
+        GeneralPurposeConfig::new()
+            .with_decode_allow_trailing_bits(true)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let bytes = engine.decode(digest).map_err(|_| invalid())?;
+    if bytes.len() != len {
+        return Err(invalid());
+    }
+    value[..len].copy_from_slice(&bytes);
+    Ok(Integrity { tag, value })
+}
+
+fn to_bytes<T>(items: &[T]) -> Result<Vec<u8>, WriteError>
+where
+    for<'a> T: BinWrite<Args<'a> = ()>,
+{
+    let mut cursor = Cursor::new(Vec::new());
+    write_all(&mut cursor, items)?;
+    Ok(cursor.into_inner())
+}
+
+fn write_all<T>(out: &mut Cursor<Vec<u8>>, items: &[T]) -> Result<(), WriteError>
+where
+    for<'a> T: BinWrite<Args<'a> = ()>,
+{
+    for item in items {
+        out.write_le(item)?;
+    }
+    Ok(())
+}
+
+/// A trailer tag, or an array written Bun's `writeArray` way: absolute
+/// `[begin, end)` offsets followed by the records. Offsets are only known
+/// once the trailer's position in the file is.
+enum TrailerPart {
+    Tag([u8; 8]),
+    Array(Vec<u8>),
+}
+
+fn write_array<T>(out: &mut Vec<TrailerPart>, items: &[T]) -> Result<(), WriteError>
+where
+    for<'a> T: BinWrite<Args<'a> = ()>,
+{
+    out.push(TrailerPart::Array(to_bytes(items)?));
+    Ok(())
+}
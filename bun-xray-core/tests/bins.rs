@@ -0,0 +1,57 @@
+use bun_xray_core::model::BinSpec;
+use bun_xray_core::{parse_any_lockfile, Lockfile, SecurityScanner};
+use std::path::PathBuf;
+
+fn text_example() -> Lockfile {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/text-lockfile/bun.lock");
+    parse_any_lockfile(&path).expect("parse")
+}
+
+fn package_mut<'a>(lock: &'a mut Lockfile, name: &str) -> &'a mut bun_xray_core::Package {
+    lock.packages.iter_mut().find(|p| p.name == name).unwrap()
+}
+
+#[test]
+fn text_bin_map_is_decoded() {
+    let mut lock = text_example();
+    let ts = package_mut(&mut lock, "typescript");
+    assert_eq!(ts.bin.executables(&ts.name), vec!["tsc", "tsserver"]);
+    let scan = lock.scan(None);
+    assert!(scan.bin_conflicts.is_empty());
+    assert!(scan.bin_shadowing.is_empty());
+}
+
+#[test]
+fn two_packages_claiming_one_executable_conflict() {
+    let mut lock = text_example();
+    package_mut(&mut lock, "@scope/util").bin = BinSpec::NamedFile {
+        name: "tsc".into(),
+        path: "evil.js".into(),
+    };
+    let scan = lock.scan(None);
+    assert_eq!(scan.bin_conflicts.len(), 1);
+    assert_eq!(scan.bin_conflicts[0].executable, "tsc");
+    let owners: Vec<_> = scan.bin_conflicts[0].packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(owners, vec!["@scope/util", "typescript"]);
+}
+
+#[test]
+fn transitive_dependency_shadowing_node_is_flagged() {
+    let mut lock = text_example();
+    // A direct dependency may ship a `node` shim without being flagged...
+    package_mut(&mut lock, "typescript").bin = BinSpec::File { path: "x".into() };
+    package_mut(&mut lock, "typescript").name = "node".into();
+    assert!(lock.scan(None).bin_shadowing.is_empty());
+
+    // ...but a package nobody asked for directly may not.
+    let mut sneaky = lock.packages[1].clone();
+    sneaky.name = "sneaky".into();
+    sneaky.bin = BinSpec::Map {
+        entries: vec![("git".into(), "git.js".into())],
+    };
+    lock.packages.push(sneaky);
+    let shadowing = lock.scan(None).bin_shadowing;
+    assert_eq!(shadowing.len(), 1);
+    assert_eq!(shadowing[0].executable, "git");
+    assert_eq!(shadowing[0].package.name, "sneaky");
+}
@@ -0,0 +1,92 @@
+use bun_xray_core::model::BinSpec;
+use bun_xray_core::{parse_lockfile_from_bytes, LockfileBuilder, SecurityScanner};
+
+const INTEGRITY: &str = "sha512-XI5MPzVNApjAyhQzphX8BkmKsKUxD4LdyK24iZeQ9wKK35qcO7lURsLUaGdDGZ2gBAV3uxzk3WhA4hSa3Qv+Ow";
+
+#[test]
+fn builds_override_attack_fixture() {
+    let mut builder = LockfileBuilder::new("app");
+    let left_pad = builder.add_package("left-pad", "1.3.0");
+    builder
+        .registry(left_pad, "https://evil.example/")
+        .integrity(left_pad, INTEGRITY)
+        .add_dependency(LockfileBuilder::ROOT, left_pad, "^1.0.0")
+        .override_dependency("left-pad", "1.3.0");
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(lock.meta_hash_matches(), Some(true));
+    assert!(lock.name_hash_mismatches.is_empty());
+
+    let pkg = &lock.packages[left_pad as usize];
+    assert_eq!(pkg.registry_url, "https://evil.example/");
+    assert_eq!(pkg.integrity_hash.as_deref(), Some(INTEGRITY));
+    assert_eq!(lock.trailers.overrides[0].name.as_deref(), Some("left-pad"));
+
+    let scan = lock.scan(None);
+    assert!(scan.untrusted_registries.iter().any(|p| p.name == "left-pad"));
+}
+
+#[test]
+fn builds_patched_and_trusted_trailers() {
+    let mut builder = LockfileBuilder::new("app");
+    let is_even = builder.add_package("is-even", "1.0.0");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, is_even, "1.0.0")
+        .patch("is-even@1.0.0", "patches/is-even.patch")
+        .trust("is-even")
+        .catalog(None, "react", "^18.0.0")
+        .catalog(Some("legacy"), "react", "^16.0.0");
+
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    let trailers = &lock.trailers;
+    assert_eq!(trailers.patched[0].name_version.as_deref(), Some("is-even@1.0.0"));
+    assert_eq!(trailers.patched[0].path, "patches/is-even.patch");
+    assert_eq!(trailers.trusted[0].name.as_deref(), Some("is-even"));
+    assert_eq!(trailers.default_catalog[0].req, "^18.0.0");
+    assert_eq!(trailers.catalogs[0].name, "legacy");
+    assert_eq!(trailers.catalogs[0].dependencies[0].req, "^16.0.0");
+    assert!(lock.scan(None).unresolved_trailer_hashes.is_empty());
+}
+
+#[test]
+fn nests_conflicting_versions_under_their_dependent() {
+    let mut builder = LockfileBuilder::new("app");
+    let a1 = builder.add_package("a", "1.0.0");
+    let b = builder.add_package("b", "1.0.0");
+    let a2 = builder.add_package("a", "2.0.0");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, a1, "^1.0.0")
+        .add_dependency(LockfileBuilder::ROOT, b, "^1.0.0")
+        .add_dependency(b, a2, "^2.0.0");
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    let by_path = lock.install_tree.by_path();
+    assert_eq!(by_path["node_modules/a"].package_id, Some(a1));
+    assert_eq!(by_path["node_modules/b/node_modules/a"].package_id, Some(a2));
+    assert!(lock.scan(None).hoisting_shadows.is_empty());
+}
+
+#[test]
+fn builds_scripts_and_named_file_bin() {
+    let mut builder = LockfileBuilder::new("app");
+    let foo = builder.add_package("foo", "1.0.0");
+    builder.package_mut(foo).scripts.postinstall = Some("node i.j".into());
+    builder.package_mut(foo).bin = BinSpec::NamedFile {
+        name: "foo-cli".into(),
+        path: "cli.js".into(),
+    };
+
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    let pkg = &lock.packages[foo as usize];
+    let scripts: Vec<_> = pkg.scripts.iter().collect();
+    assert_eq!(scripts, vec![("postinstall", "node i.j")]);
+    assert_eq!(
+        pkg.bin,
+        BinSpec::NamedFile {
+            name: "foo-cli".into(),
+            path: "cli.js".into()
+        }
+    );
+}
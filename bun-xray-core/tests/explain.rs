@@ -0,0 +1,68 @@
+use bun_xray_core::{explain_lockfile, ParseError, Region};
+use common::{rich_fixture, SCRIPT};
+
+mod common;
+
+fn find<'a>(regions: &'a [Region], path: &str) -> &'a Region {
+    regions
+        .iter()
+        .find(|r| r.path == path)
+        .unwrap_or_else(|| panic!("no region {path}"))
+}
+
+#[test]
+fn every_byte_is_accounted_for() {
+    let bytes = rich_fixture();
+    let regions = explain_lockfile(&bytes).unwrap();
+
+    // Top-level regions tile the file; nested ones sit inside a container.
+    let mut covered = 0;
+    for region in &regions {
+        assert!(region.start <= covered, "gap before {region:?}");
+        covered = covered.max(region.end);
+    }
+    assert_eq!(covered, bytes.len() as u64);
+    assert!(regions.iter().all(|r| r.path != "unaccounted"), "{regions:#?}");
+
+    assert_eq!(find(&regions, "header.format_version").value, "3");
+    let name = find(&regions, "packages[1].name");
+    assert_eq!(name.value, "left-pad-but-longer");
+    assert_eq!(name.package.as_ref().unwrap().index, 1);
+    assert!(find(&regions, "packages[1].resolution").value.starts_with("npm 1.3.0"));
+    assert!(find(&regions, "buffers.dependencies[0]").value.ends_with("→ packages[1]"));
+    assert_eq!(find(&regions, "sentinel").value, "0");
+    assert!(regions.iter().any(|r| r.path == "trailers.trusted"));
+}
+
+#[test]
+fn external_strings_point_back_at_their_fields() {
+    let bytes = rich_fixture();
+    let regions = explain_lockfile(&bytes).unwrap();
+
+    let script = regions
+        .iter()
+        .find(|r| r.path.starts_with("buffers.string_bytes[") && r.value == SCRIPT)
+        .unwrap();
+    assert_eq!(&bytes[script.start as usize..script.end as usize], script.value.as_bytes());
+    assert!(script
+        .notes
+        .contains(&"referenced by packages[1].scripts.postinstall".to_string()));
+    assert_eq!(script.package.as_ref().unwrap().name, "left-pad-but-longer");
+}
+
+#[test]
+fn slack_bytes_and_damage_are_annotated() {
+    let mut bytes = rich_fixture();
+    let sentinel = find(&explain_lockfile(&bytes).unwrap(), "sentinel").start as usize;
+    bytes[sentinel..sentinel + 4].copy_from_slice(b"evil");
+    bytes.extend_from_slice(b"payload");
+    let regions = explain_lockfile(&bytes).unwrap();
+
+    assert!(!find(&regions, "sentinel").notes.is_empty());
+    let trailing = find(&regions, "unaccounted");
+    assert_eq!(trailing.len(), 7);
+    assert_eq!(trailing.end, bytes.len() as u64);
+
+    let err = explain_lockfile(b"{\"lockfileVersion\": 1}").unwrap_err();
+    assert!(matches!(err.kind(), ParseError::InvalidMagic));
+}
@@ -0,0 +1,121 @@
+use bun_xray_core::model::{BinSpec, PackageColumn};
+use bun_xray_core::{parse_lockfile_from_bytes, write_lockfile, LockfileBuilder, ParseError};
+
+const FORMAT_POS: usize = 42;
+const FIELD_COUNT_POS: usize = 86 + 16;
+const TREE_RECORD_SIZE: usize = 20;
+
+fn read_u32(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(buf: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+/// Root depends on a@1 and b; b depends on a@2, which gets nested.
+fn nested_fixture() -> Vec<u8> {
+    let mut builder = LockfileBuilder::new("app");
+    let a1 = builder.add_package("a", "1.0.0");
+    let b = builder.add_package("b", "1.0.0");
+    let a2 = builder.add_package("a", "2.0.0");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, a1, "^1.0.0")
+        .add_dependency(LockfileBuilder::ROOT, b, "^1.0.0")
+        .add_dependency(b, a2, "^2.0.0");
+    builder.package_mut(b).bin = BinSpec::File {
+        path: "cli.js".into(),
+    };
+    builder.to_bytes().unwrap()
+}
+
+/// Rewrites a current lockfile as an older revision in place: columns past
+/// `field_count` become dead space before the buffers, and for package-id
+/// revisions tree owners and hoisted entries are mapped through the
+/// resolutions buffer.
+fn downgrade(mut bytes: Vec<u8>, version: u32, field_count: u64, package_ids: bool) -> Vec<u8> {
+    bytes[FORMAT_POS..FORMAT_POS + 4].copy_from_slice(&version.to_le_bytes());
+    bytes[FIELD_COUNT_POS..FIELD_COUNT_POS + 8].copy_from_slice(&field_count.to_le_bytes());
+    if !package_ids {
+        return bytes;
+    }
+
+    // Pointer block order: dependencies, extern strings, trees, hoisted,
+    // resolutions, string bytes.
+    let ptrs = read_u64(&bytes, 86 + 32) as usize;
+    let range = |i: usize| {
+        let at = ptrs + i * 16;
+        (read_u64(&bytes, at) as usize, read_u64(&bytes, at + 8) as usize)
+    };
+    let (trees_begin, trees_end) = range(2);
+    let (hoisted_begin, hoisted_end) = range(3);
+    let (res_begin, _) = range(4);
+    let resolve = |bytes: &[u8], dep_id: u32| read_u32(bytes, res_begin + dep_id as usize * 4);
+
+    for at in (trees_begin..trees_end).step_by(TREE_RECORD_SIZE) {
+        let owner = read_u32(&bytes, at + 4);
+        if owner != u32::MAX {
+            let package_id = resolve(&bytes, owner);
+            bytes[at + 4..at + 8].copy_from_slice(&package_id.to_le_bytes());
+        }
+    }
+    for at in (hoisted_begin..hoisted_end).step_by(4) {
+        let package_id = resolve(&bytes, read_u32(&bytes, at));
+        bytes[at..at + 4].copy_from_slice(&package_id.to_le_bytes());
+    }
+    bytes
+}
+
+fn layout(lock: &bun_xray_core::Lockfile) -> Vec<(String, Option<u32>)> {
+    lock.install_tree
+        .nodes
+        .iter()
+        .flat_map(|node| node.entries.iter().map(|e| (e.path.clone(), e.package_id)))
+        .collect()
+}
+
+#[test]
+fn decodes_every_known_revision() {
+    let current = nested_fixture();
+    let (expected, _) = parse_lockfile_from_bytes(&current).unwrap();
+    assert_eq!(expected.format_version, 3);
+    assert_eq!(expected.columns.len(), 8);
+    assert!(layout(&expected).contains(&("node_modules/b/node_modules/a".into(), Some(3))));
+
+    for (version, field_count, package_ids) in [(2, 7, false), (1, 7, true), (0, 6, true)] {
+        let bytes = downgrade(current.clone(), version, field_count, package_ids);
+        let (lock, warnings) = parse_lockfile_from_bytes(&bytes).unwrap();
+        assert!(warnings.is_empty(), "v{version}: {warnings:?}");
+        assert_eq!(lock.format_version, version);
+        assert_eq!(lock.columns.len(), field_count as usize);
+        assert!(!lock.columns.contains(&PackageColumn::Scripts));
+        assert_eq!(layout(&lock), layout(&expected), "v{version}");
+        assert_eq!(lock.meta_hash_matches(), Some(true));
+
+        // The writer upgrades older revisions to the current layout.
+        let (upgraded, _) = parse_lockfile_from_bytes(&write_lockfile(&lock).unwrap()).unwrap();
+        assert_eq!(upgraded.format_version, 3);
+        assert_eq!(layout(&upgraded), layout(&expected), "v{version} upgraded");
+    }
+
+    let (v0, _) = parse_lockfile_from_bytes(&downgrade(current, 0, 6, true)).unwrap();
+    assert_eq!(v0.columns.last(), Some(&PackageColumn::Meta));
+    assert_eq!(v0.packages[2].bin, BinSpec::None);
+}
+
+#[test]
+fn rejects_unknown_revisions_and_column_counts() {
+    let current = nested_fixture();
+    let err = parse_lockfile_from_bytes(&downgrade(current.clone(), 4, 8, false)).unwrap_err();
+    assert!(matches!(err.kind(), ParseError::UnsupportedFormat(4)));
+    assert_eq!(err.offset(), Some(FORMAT_POS as u64));
+
+    let err = parse_lockfile_from_bytes(&downgrade(current, 2, 8, false)).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ParseError::UnexpectedColumns {
+            format: 2,
+            field_count: 8
+        }
+    ));
+}
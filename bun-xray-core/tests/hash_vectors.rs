@@ -0,0 +1,24 @@
+use bun_xray_core::hash::wyhash11;
+
+#[test]
+fn wyhash11_matches_reference_vectors() {
+    assert_eq!(wyhash11(0, b""), 0x0);
+    assert_eq!(wyhash11(1, b"a"), 0xbed235177f41d328);
+    assert_eq!(wyhash11(2, b"abc"), 0xbe348debe59b27c3);
+    assert_eq!(wyhash11(3, b"message digest"), 0x37320f657213a290);
+    assert_eq!(wyhash11(4, b"abcdefghijklmnopqrstuvwxyz"), 0xd0b270e1d8a7019c);
+    assert_eq!(
+        wyhash11(
+            5,
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"
+        ),
+        0x602a1894d3bbfe7f
+    );
+    assert_eq!(
+        wyhash11(
+            6,
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+        ),
+        0x829e9c148b75970e
+    );
+}
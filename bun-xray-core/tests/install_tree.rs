@@ -0,0 +1,58 @@
+use bun_xray_core::model::InstallTree;
+use bun_xray_core::{parse_any_lockfile, SecurityScanner};
+use std::path::PathBuf;
+
+fn text_example() -> bun_xray_core::Lockfile {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/text-lockfile/bun.lock");
+    parse_any_lockfile(&path).expect("parse")
+}
+
+#[test]
+fn text_lockfile_install_paths() {
+    let lock = text_example();
+    let by_path = lock.install_tree.by_path();
+    let nested = by_path["node_modules/web/node_modules/left-pad"];
+    let pkg = &lock.packages[nested.package_id.unwrap() as usize];
+    assert_eq!(pkg.version, "1.1.3");
+    assert!(by_path.contains_key("node_modules/@scope/util"));
+    assert!(lock.scan(None).hoisting_shadows.is_empty());
+}
+
+#[test]
+fn lookup_dirs_walk_up_to_root() {
+    assert_eq!(InstallTree::lookup_dirs(""), vec!["node_modules"]);
+    assert_eq!(
+        InstallTree::lookup_dirs("node_modules/a/node_modules/@s/b"),
+        vec![
+            "node_modules/a/node_modules/@s/b/node_modules",
+            "node_modules/a/node_modules",
+            "node_modules",
+        ]
+    );
+}
+
+#[test]
+fn hoisted_copy_shadowing_resolution_is_reported() {
+    let mut lock = text_example();
+    let evil_id = lock
+        .packages
+        .iter()
+        .position(|p| p.name == "left-pad" && p.version == "1.1.3")
+        .unwrap() as u32;
+    // Hoist the foreign-registry copy into the root node_modules.
+    for node in &mut lock.install_tree.nodes {
+        for entry in &mut node.entries {
+            if entry.path == "node_modules/left-pad" {
+                entry.package_id = Some(evil_id);
+            }
+        }
+    }
+    let shadows = lock.scan(None).hoisting_shadows;
+    let root_shadow = shadows
+        .iter()
+        .find(|s| s.dependent_path.is_empty())
+        .expect("root edge shadowed");
+    assert_eq!(root_shadow.expected.version, "1.3.0");
+    assert_eq!(root_shadow.loaded.version, "1.1.3");
+    assert_eq!(root_shadow.loaded_path, "node_modules/left-pad");
+}
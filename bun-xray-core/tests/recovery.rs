@@ -0,0 +1,136 @@
+use bun_xray_core::model::{PackageColumn, ResolutionKind};
+use bun_xray_core::{
+    hash, parse_lockfile_from_bytes, parse_lockfile_from_bytes_with_options, ParseError, ParseOptions, ParseWarning,
+};
+use common::{
+    npm_fixture, package_table, read_u64, DEPENDENCY_RECORD_SIZE, HEADER_LEN, META_RECORD_SIZE, NAME_HASH_RECORD_SIZE,
+    NAME_RECORD_SIZE, PACKAGES_END_AT, PACKAGE_COUNT_AT, RESOLUTION_RECORD_SIZE, SLICE_RECORD_SIZE,
+};
+use proptest::prelude::*;
+
+mod common;
+
+const RECOVER: ParseOptions = ParseOptions {
+    strict: false,
+    recover: true,
+};
+
+#[test]
+fn bad_string_pointer_only_damages_its_package() {
+    let mut bytes = npm_fixture();
+    let (len, begin) = package_table(&bytes);
+    // Names and name hashes precede the resolution column; inside package 2's
+    // record the npm version's pre-release string sits 40 bytes in.
+    let pre_at = begin + (NAME_RECORD_SIZE + NAME_HASH_RECORD_SIZE) * len + RESOLUTION_RECORD_SIZE * 2 + 40;
+    let pointer = (1u64 << 63) | (4u64 << 32) | 0xffff;
+    bytes[pre_at..pre_at + 8].copy_from_slice(&pointer.to_le_bytes());
+
+    let err = parse_lockfile_from_bytes(&bytes).unwrap_err();
+    assert!(matches!(err.kind(), ParseError::StringPointer(0xffff, 4)));
+
+    let (lock, damage) = parse_lockfile_from_bytes_with_options(&bytes, RECOVER).unwrap();
+    assert_eq!(lock.packages.len(), 4);
+    assert_eq!(damage.len(), 1, "{damage:?}");
+    let ParseWarning::Damaged {
+        offset,
+        path,
+        package,
+        ..
+    } = &damage[0]
+    else {
+        panic!("unexpected warning {:?}", damage[0]);
+    };
+    assert_eq!(*offset, pre_at as u64);
+    assert_eq!(path, "packages[2].resolution.npm.version.pre");
+    assert_eq!(package.as_ref().map(|p| p.name.as_str()), Some("b"));
+
+    let damaged = &lock.packages[2];
+    assert_eq!(damaged.name, "b");
+    assert_eq!(damaged.resolution, ResolutionKind::Unknown(String::new()));
+    assert_eq!(lock.packages[3].version, "1.0.0");
+    assert_eq!(lock.packages[0].dependencies.len(), 3);
+}
+
+#[test]
+fn truncated_file_keeps_decodable_rows() {
+    let full = npm_fixture();
+    let (len, begin) = package_table(&full);
+    // Cut inside the meta column, after the first two rows.
+    let before_meta = NAME_RECORD_SIZE + NAME_HASH_RECORD_SIZE + RESOLUTION_RECORD_SIZE + 2 * SLICE_RECORD_SIZE;
+    let meta_at = begin + before_meta * len;
+    let bytes = &full[..meta_at + 2 * META_RECORD_SIZE + 10];
+
+    assert!(parse_lockfile_from_bytes(bytes).is_err());
+    let (lock, damage) = parse_lockfile_from_bytes_with_options(bytes, RECOVER).unwrap();
+    let names: Vec<&str> = lock.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["app", "a", "b", "c"]);
+    assert!(damage.iter().any(|w| matches!(
+        w,
+        ParseWarning::ColumnTruncated {
+            column: PackageColumn::Meta,
+            decoded: 2,
+            expected: 4,
+            ..
+        }
+    )));
+    assert!(damage.iter().any(|w| w.code() == "damaged" && w.structure() == "header"));
+    // The string and dependency buffers were cut off, so edges are lost.
+    assert!(lock.packages[0].dependencies.is_empty());
+
+    let strict_recover = ParseOptions {
+        strict: true,
+        recover: true,
+    };
+    let err = parse_lockfile_from_bytes_with_options(bytes, strict_recover).unwrap_err();
+    assert!(matches!(err.kind(), ParseError::Anomalies(_)));
+}
+
+#[test]
+fn dropped_dependency_does_not_shift_name_hash_checks() {
+    let mut bytes = npm_fixture();
+    // The pointer block follows the package table; dependencies come first.
+    let dependencies_at = read_u64(&bytes, read_u64(&bytes, PACKAGES_END_AT) as usize) as usize;
+    let record = |i: usize| dependencies_at + i * DEPENDENCY_RECORD_SIZE;
+    // Break the middle dependency's name and tamper with the last one's
+    // stored name hash, which follows its 8-byte name.
+    let (middle, last) = (record(1), record(2));
+    let pointer = (1u64 << 63) | (4u64 << 32) | 0xffff;
+    bytes[middle..middle + 8].copy_from_slice(&pointer.to_le_bytes());
+    bytes[last + 8..last + 16].copy_from_slice(&hash::name_hash("x").to_le_bytes());
+
+    let (lock, damage) = parse_lockfile_from_bytes_with_options(&bytes, RECOVER).unwrap();
+    assert!(damage.iter().any(|w| w.code() == "damaged"), "{damage:?}");
+    let names: Vec<&str> = lock.packages[0].dependencies.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["a", "c"]);
+    assert_eq!(lock.name_hash_mismatches.len(), 1, "{:?}", lock.name_hash_mismatches);
+    let mismatch = &lock.name_hash_mismatches[0];
+    assert_eq!(mismatch.name, "c");
+    assert_eq!(mismatch.dependency_index, Some(1));
+    assert_eq!(mismatch.stored, hash::name_hash("x"));
+}
+
+#[test]
+fn corrupt_package_count_and_end_are_salvaged() {
+    let mut bytes = npm_fixture();
+    bytes[PACKAGE_COUNT_AT..PACKAGE_COUNT_AT + 8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+    assert!(parse_lockfile_from_bytes(&bytes).is_err());
+    let (lock, damage) = parse_lockfile_from_bytes_with_options(&bytes, RECOVER).unwrap();
+    assert!(!damage.is_empty());
+    assert!(lock.packages.len() <= bytes.len() / NAME_RECORD_SIZE);
+
+    let mut bytes = npm_fixture();
+    bytes[PACKAGES_END_AT..PACKAGES_END_AT + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    let (lock, damage) = parse_lockfile_from_bytes_with_options(&bytes, RECOVER).unwrap();
+    assert!(!damage.is_empty());
+    assert_eq!(lock.packages.len(), 4);
+}
+
+proptest! {
+    #[test]
+    fn recovers_from_any_truncation_past_the_header(cut in HEADER_LEN..512usize) {
+        let full = npm_fixture();
+        let bytes = &full[..cut.min(full.len())];
+        let (lock, _) = parse_lockfile_from_bytes_with_options(bytes, RECOVER).unwrap();
+        prop_assert!(lock.packages.len() <= 4);
+    }
+}
@@ -0,0 +1,143 @@
+use bun_xray_core::model::{BehaviorFlags, DependencySpec};
+use bun_xray_core::semver::{satisfies, Range, Version};
+use bun_xray_core::{parse_lockfile_from_bytes, LockfileBuilder, SecurityScanner};
+
+fn check(range: &str, yes: &[&str], no: &[&str]) {
+    for version in yes {
+        assert_eq!(satisfies(version, range), Some(true), "{version} should satisfy {range}");
+    }
+    for version in no {
+        assert_eq!(satisfies(version, range), Some(false), "{version} should not satisfy {range}");
+    }
+}
+
+#[test]
+fn caret_and_tilde() {
+    check("^1.2.3", &["1.2.3", "1.9.0"], &["1.2.2", "2.0.0", "2.0.0-alpha"]);
+    check("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2"]);
+    check("^0.0.3", &["0.0.3"], &["0.0.4"]);
+    check("^0.x", &["0.0.1", "0.9.9"], &["1.0.0"]);
+    check("^0.0", &["0.0.7"], &["0.1.0"]);
+    check("^1.2.3-beta.2", &["1.2.3-beta.2", "1.2.3-beta.10", "1.5.0"], &["1.2.3-beta.1", "1.2.4-beta.3"]);
+    check("~1.2.3", &["1.2.3", "1.2.9"], &["1.3.0"]);
+    check("~1", &["1.0.0", "1.9.9"], &["2.0.0"]);
+    check("~> 1.2", &["1.2.0", "1.2.5"], &["1.3.0"]);
+}
+
+#[test]
+fn x_ranges_hyphens_and_primitives() {
+    check("1.x", &["1.0.0", "1.99.0"], &["2.0.0", "0.9.0"]);
+    check("1.2.*", &["1.2.0", "1.2.8"], &["1.3.0"]);
+    check("*", &["0.0.0", "10.1.2"], &["1.0.0-rc.1"]);
+    check("", &["3.0.0"], &[]);
+    check("1.2 - 2.3", &["1.2.0", "2.3.9"], &["2.4.0", "1.1.9"]);
+    check("1.2.3 - 2.3.4", &["2.3.4"], &["2.3.5", "1.2.2"]);
+    check(">1.2", &["1.3.0"], &["1.2.9"]);
+    check("<=1.2", &["1.2.9"], &["1.3.0"]);
+    check(">= 1.0.0 < 1.5.0", &["1.4.9"], &["1.5.0"]);
+    check("1.2.3", &["1.2.3", "v1.2.3", "1.2.3+build.9"], &["1.2.4"]);
+    check("<1.0.0 || >=2.1.0", &["0.5.0", "2.1.0"], &["1.5.0", "2.0.9"]);
+    check("1.2.3-beta.1 || ^2", &["1.2.3-beta.1", "2.4.0"], &["1.2.3-beta.2", "2.5.0-rc.1"]);
+}
+
+#[test]
+fn prerelease_ordering() {
+    let expected = [
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+    ];
+    let mut versions: Vec<Version> = expected.iter().rev().map(|v| Version::parse(v).unwrap()).collect();
+    versions.sort();
+    let sorted: Vec<String> = versions.iter().map(Version::to_string).collect();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn non_ranges_do_not_parse() {
+    let specs = ["latest", "next", "github:o/r", "workspace:*", "file:../x", "https://x/y.tgz", "1.2.3 || beta"];
+    for spec in specs.into_iter().chain([">="]) {
+        assert_eq!(Range::parse(spec), None, "{spec}");
+    }
+    assert_eq!(Range::parse("^1.2").unwrap().to_string(), ">=1.2.0 <2.0.0-0");
+    // No component can be bumped past u64::MAX.
+    let huge = [
+        "^18446744073709551615",
+        "~1.18446744073709551615",
+        "^0.0.18446744073709551615",
+        "1.x || 99999999999999999999",
+    ];
+    for spec in huge {
+        assert_eq!(Range::parse(spec), None, "{spec}");
+    }
+    assert!(Version::parse("18446744073709551615.0.0").is_none());
+}
+
+#[test]
+fn downgrades_are_range_violations() {
+    let mut builder = LockfileBuilder::new("app");
+    let lodash = builder.add_package("lodash", "4.17.4");
+    let react = builder.add_package("react", "18.2.0");
+    let dom = builder.add_package("react-dom", "17.0.2");
+    let types = builder.add_package("@types/node", "18.0.0");
+    builder
+        .add_dependency(LockfileBuilder::ROOT, lodash, "^4.17.21")
+        .add_dependency(LockfileBuilder::ROOT, react, "^18.0.0")
+        .add_dependency(LockfileBuilder::ROOT, dom, "^17")
+        .add_dependency_with(dom, react, "17.0.2", BehaviorFlags::PEER)
+        .add_dependency(LockfileBuilder::ROOT, types, "npm:@types/node@^20");
+
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    let scan = lock.scan(None);
+    let found: Vec<(&str, &str, &str, &str)> = scan
+        .range_violations
+        .iter()
+        .map(|v| (v.dependent.name.as_str(), v.req.as_str(), v.range.as_str(), v.resolved.version.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("app", "^4.17.21", ">=4.17.21 <5.0.0-0", "4.17.4"),
+            ("app", "npm:@types/node@^20", ">=20.0.0 <21.0.0-0", "18.0.0"),
+            ("react-dom", "17.0.2", "=17.0.2", "18.2.0"),
+        ]
+    );
+    assert!(scan.range_violations[2].behavior.contains(BehaviorFlags::PEER));
+
+    // An override is a deliberate out-of-range resolution.
+    builder.override_dependency("lodash", "4.17.4");
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    assert!(lock.scan(None).range_violations.iter().all(|v| v.dependency != "lodash"));
+}
+
+#[test]
+fn unbumpable_ranges_are_not_checked() {
+    let text = r#"{
+      "lockfileVersion": 1,
+      "workspaces": { "": { "name": "app", "dependencies": { "a": "^18446744073709551615" } } },
+      "packages": { "a": ["a@1.0.0", "", {}, "sha512-AAAA"] }
+    }"#;
+    let (lock, _) = parse_lockfile_from_bytes(text.as_bytes()).unwrap();
+    assert_eq!(lock.packages[0].dependencies[0].req, "^18446744073709551615");
+    assert!(lock.scan(None).range_violations.is_empty());
+}
+
+#[test]
+fn stored_version_tag_decides_what_is_checked() {
+    let mut builder = LockfileBuilder::new("app");
+    let a = builder.add_package("a", "2.0.0");
+    builder.add_dependency(LockfileBuilder::ROOT, a, "1.0.0");
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    assert_eq!(lock.scan(None).range_violations.len(), 1);
+
+    // Bun tagged the literal as a dist-tag, so there is no range to violate.
+    let dep = &mut builder.package_mut(LockfileBuilder::ROOT).dependencies[0];
+    dep.spec = DependencySpec::DistTag { tag: "1.0.0".into() };
+    let (lock, _) = parse_lockfile_from_bytes(&builder.to_bytes().unwrap()).unwrap();
+    assert_eq!(lock.packages[0].dependencies[0].spec.tag(), 2);
+    assert!(lock.scan(None).range_violations.is_empty());
+}
@@ -0,0 +1,52 @@
+use bun_xray_core::parse_lockfile_from_bytes;
+use common::{npm_fixture, read_u64, PACKAGES_END_AT};
+
+mod common;
+
+#[test]
+fn writer_layout_leaves_only_alignment_padding() {
+    let bytes = npm_fixture();
+    let (lock, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    let coverage = &lock.coverage;
+    assert!(coverage.slack.iter().all(|s| s.is_alignment_padding()), "{:?}", coverage.slack);
+    assert!(coverage.overlaps.is_empty());
+
+    let structures: Vec<&str> = coverage.spans.iter().map(|s| s.structure.as_str()).collect();
+    for expected in ["header", "packages.name", "buffers.pointers", "buffers.string_bytes", "sentinel"] {
+        assert!(structures.contains(&expected), "{expected} missing from {structures:?}");
+    }
+    assert_eq!(structures.last(), Some(&"trailers.trusted"));
+}
+
+#[test]
+fn appended_payload_is_slack_with_its_entropy() {
+    let mut bytes = npm_fixture();
+    let end = bytes.len() as u64;
+    let payload: Vec<u8> = (0..=255u8).collect();
+    bytes.extend_from_slice(&payload);
+
+    let (lock, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    let slack = lock.coverage.slack.last().unwrap();
+    assert_eq!((slack.offset, slack.len), (end, 256));
+    assert!(!slack.zeroed);
+    assert!((slack.entropy - 8.0).abs() < 1e-9, "{}", slack.entropy);
+}
+
+#[test]
+fn overlapping_buffers_are_reported() {
+    let mut bytes = npm_fixture();
+    // Point the extern strings buffer at the first record of the
+    // dependencies buffer, which comes first in the pointer block.
+    let pointers = read_u64(&bytes, PACKAGES_END_AT) as usize;
+    let deps_begin = read_u64(&bytes, pointers);
+    bytes[pointers + 16..pointers + 24].copy_from_slice(&deps_begin.to_le_bytes());
+    bytes[pointers + 24..pointers + 32].copy_from_slice(&(deps_begin + 16).to_le_bytes());
+
+    let (lock, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    let [(first, second)] = lock.coverage.overlaps.as_slice() else {
+        panic!("{:?}", lock.coverage.overlaps);
+    };
+    let mut pair = [first.structure.as_str(), second.structure.as_str()];
+    pair.sort();
+    assert_eq!(pair, ["buffers.dependencies", "buffers.extern_strings"]);
+}
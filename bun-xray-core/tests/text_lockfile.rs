@@ -0,0 +1,77 @@
+use bun_xray_core::model::{LockfileFormat, OsFlags, PackageOrigin, ResolutionKind};
+use bun_xray_core::{detect_format, hash, parse_any_lockfile_with_warnings, SecurityScanner};
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+#[test]
+fn parses_text_lockfile_into_model() {
+    let (lock, warnings) =
+        parse_any_lockfile_with_warnings(&example("text-lockfile/bun.lock")).expect("parse");
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(lock.source_format, LockfileFormat::Text);
+    assert_eq!(lock.format_version, 1);
+
+    let root = &lock.packages[0];
+    assert_eq!(root.name, "text-demo");
+    assert_eq!(root.resolution, ResolutionKind::Root);
+
+    let util = lock.packages.iter().find(|p| p.name == "@scope/util").unwrap();
+    assert_eq!(util.version, "2.1.0");
+    assert_eq!(util.registry_url, "https://registry.npmjs.org/");
+    assert!(util.integrity_hash.as_deref().unwrap().starts_with("sha512-"));
+    assert_eq!(util.meta.os, OsFlags::DARWIN | OsFlags::LINUX);
+    assert_eq!(util.meta.arch.label(), "arm64");
+    assert_eq!(util.meta.origin, PackageOrigin::Npm);
+
+    let web = lock.packages.iter().find(|p| p.name == "web").unwrap();
+    assert_eq!(
+        web.resolution,
+        ResolutionKind::Workspace {
+            name: "packages/web".into()
+        }
+    );
+    // The workspace resolves its own nested left-pad, the root gets the hoisted one.
+    let web_dep = &lock.packages[web.dependencies[0].resolved_package_id.unwrap() as usize];
+    assert_eq!(web_dep.version, "1.1.3");
+    let root_dep = root.dependencies.iter().find(|d| d.name == "left-pad").unwrap();
+    assert_eq!(lock.packages[root_dep.resolved_package_id.unwrap() as usize].version, "1.3.0");
+
+    assert_eq!(lock.trailers.trusted_hashes, vec![hash::trusted_hash("left-pad")]);
+    assert_eq!(lock.trailers.overrides[0].name_hash, hash::name_hash("typescript"));
+    assert_eq!(lock.trailers.patched[0].name_version_hash, hash::name_hash("left-pad@1.3.0"));
+    assert_eq!(lock.trailers.workspaces_count, 1);
+}
+
+#[test]
+fn text_lockfile_runs_through_scanner() {
+    let lock = bun_xray_core::parse_any_lockfile(&example("text-lockfile/bun.lock")).unwrap();
+    let scan = lock.scan(None);
+    assert!(scan
+        .untrusted_registries
+        .iter()
+        .any(|p| p.registry_url.contains("evil.example")));
+}
+
+#[test]
+fn detects_formats() {
+    let text = std::fs::read(example("text-lockfile/bun.lock")).unwrap();
+    assert_eq!(detect_format(&text), Some(LockfileFormat::Text));
+    let binary = std::fs::read(example("minimal/bun.lockb")).unwrap();
+    assert_eq!(detect_format(&binary), Some(LockfileFormat::Binary));
+    assert_eq!(detect_format(b"garbage"), None);
+}
+
+#[test]
+fn text_lockfile_rejects_invalid_json() {
+    let mut tmp = NamedTempFile::new().unwrap();
+    tmp.write_all(b"{ \"lockfileVersion\": 1, \"packages\": { ").unwrap();
+    let err = parse_any_lockfile_with_warnings(tmp.path()).unwrap_err();
+    assert!(format!("{err}").contains("not valid JSON"));
+}
@@ -0,0 +1,40 @@
+use bun_xray_core::{parse_any_lockfile, SecurityScanner};
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+#[test]
+fn resolves_trailer_hashes_to_packages() {
+    let lock = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let trailers = &lock.trailers;
+    assert_eq!(trailers.trusted.len(), 1);
+    assert_eq!(trailers.trusted[0].name.as_deref(), Some("left-pad"));
+    assert_eq!(trailers.overrides[0].name.as_deref(), Some("typescript"));
+    assert_eq!(trailers.patched[0].name_version.as_deref(), Some("left-pad@1.3.0"));
+    assert!(lock.scan(None).unresolved_trailer_hashes.is_empty());
+}
+
+#[test]
+fn reports_hashes_that_match_nothing() {
+    let src = std::fs::read_to_string(example("text-lockfile/bun.lock")).unwrap();
+    let src = src
+        .replace(r#""left-pad",
+  ],"#, r#""left-pad",
+    "ghost-pkg",
+  ],"#)
+        .replace(r#""left-pad@1.3.0": "patches"#, r#""left-pad@9.9.9": "patches"#);
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(src.as_bytes()).unwrap();
+
+    let lock = parse_any_lockfile(file.path()).expect("parse");
+    let unresolved = lock.scan(None).unresolved_trailer_hashes;
+    let kinds: Vec<&str> = unresolved.iter().map(|u| u.trailer).collect();
+    assert_eq!(kinds, vec!["trusted", "patched"]);
+    assert_eq!(lock.trailers.trusted[1].name, None);
+}
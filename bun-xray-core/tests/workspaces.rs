@@ -0,0 +1,65 @@
+use bun_xray_core::{hash, parse_any_lockfile, SecurityScanner};
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+#[test]
+fn decodes_workspace_entries() {
+    let lock = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let workspaces = &lock.trailers.workspaces;
+    assert_eq!(workspaces.len(), 1);
+    let web = &workspaces[0];
+    assert_eq!(web.name.as_deref(), Some("web"));
+    assert_eq!(web.version.as_deref(), Some("0.1.0"));
+    assert_eq!(web.path.as_deref(), Some("packages/web"));
+    assert_eq!(web.name_hash, hash::name_hash("web"));
+    assert_eq!(lock.trailers.workspaces_count, 1);
+
+    let scan = lock.scan(None);
+    assert!(scan.undeclared_workspaces.is_empty());
+}
+
+#[test]
+fn attributes_packages_to_workspaces() {
+    let lock = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let membership = lock.workspace_membership();
+    let owners = |name: &str, version: &str| {
+        let id = lock
+            .packages
+            .iter()
+            .position(|p| p.name == name && p.version == version)
+            .unwrap();
+        membership[id].iter().cloned().collect::<Vec<_>>()
+    };
+    assert_eq!(owners("left-pad", "1.1.3"), vec!["web"]);
+    assert_eq!(owners("left-pad", "1.3.0"), vec!["text-demo"]);
+    assert_eq!(owners("@scope/util", "2.1.0"), vec!["text-demo"]);
+}
+
+#[test]
+fn flags_workspace_resolution_missing_from_trailer() {
+    let src = std::fs::read_to_string(example("text-lockfile/bun.lock")).unwrap();
+    let src = src.replace(
+        r#""web": ["web@workspace:packages/web"]"#,
+        r#""web": ["web@workspace:vendor/web"]"#,
+    );
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(src.as_bytes()).unwrap();
+
+    let lock = parse_any_lockfile(file.path()).expect("parse");
+    let scan = lock.scan(None);
+    assert_eq!(scan.undeclared_workspaces.len(), 1);
+    let undeclared = &scan.undeclared_workspaces[0];
+    assert_eq!(undeclared.package.name, "web");
+    assert_eq!(undeclared.path, "vendor/web");
+    assert_eq!(
+        undeclared.to_string(),
+        "Workspace path vendor/web is not declared in the workspace trailer"
+    );
+}
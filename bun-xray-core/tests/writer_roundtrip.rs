@@ -0,0 +1,111 @@
+use bun_xray_core::model::{PatchedEntry, ResolutionKind};
+use bun_xray_core::{parse_any_lockfile, parse_lockfile_from_bytes, write_lockfile, Lockfile};
+use std::path::PathBuf;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+fn roundtrip(lock: &Lockfile) -> Lockfile {
+    let bytes = write_lockfile(lock).expect("write");
+    let (reparsed, warnings) = parse_lockfile_from_bytes(&bytes).expect("reparse");
+    assert!(warnings.is_empty(), "{warnings:?}");
+    reparsed
+}
+
+/// Leaves out the coverage map: it describes the byte layout, which the
+/// writer does not reproduce (the examples pad differently).
+fn as_json(lock: &Lockfile) -> serde_json::Value {
+    let mut value = serde_json::to_value(lock).unwrap();
+    value.as_object_mut().unwrap().remove("coverage");
+    value
+}
+
+#[test]
+fn binary_examples_roundtrip_losslessly() {
+    for name in ["minimal/bun.lockb", "tampered-registry/bun.lockb"] {
+        let original = parse_any_lockfile(&example(name)).expect("parse");
+        let reparsed = roundtrip(&original);
+        assert_eq!(as_json(&reparsed), as_json(&original), "{name}");
+    }
+}
+
+#[test]
+fn text_example_converts_to_stable_binary() {
+    let text = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let binary = roundtrip(&text);
+
+    assert_eq!(binary.packages.len(), text.packages.len());
+    for (b, t) in binary.packages.iter().zip(&text.packages) {
+        assert_eq!(b.name, t.name);
+        assert_eq!(b.resolution, t.resolution);
+        assert_eq!(b.dependencies, t.dependencies);
+        assert_eq!(b.bin, t.bin);
+        assert_eq!(b.meta, t.meta);
+    }
+    assert_eq!(binary.meta_hash_matches(), Some(true));
+    assert_eq!(binary.trailers.trusted, text.trailers.trusted);
+    assert_eq!(binary.trailers.workspaces, text.trailers.workspaces);
+    let paths = |lock: &Lockfile| {
+        let mut paths: Vec<(String, Option<u32>)> = lock
+            .install_tree
+            .entries()
+            .map(|e| (e.path.clone(), e.package_id))
+            .collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(paths(&binary), paths(&text));
+
+    // Once in binary form, further round trips are exact.
+    assert_eq!(as_json(&roundtrip(&binary)), as_json(&binary));
+}
+
+#[test]
+fn patch_hash_flag_uses_bun_meaning() {
+    let mut lock = parse_any_lockfile(&example("minimal/bun.lockb")).expect("parse");
+    let patch = |name: &str, patch_hash| PatchedEntry {
+        name_version_hash: 0,
+        name_version: None,
+        path: format!("patches/{name}@1.0.0.patch"),
+        patch_hash,
+    };
+    lock.trailers.patched = vec![patch("a", Some(0x1122_3344_5566_7788)), patch("b", None)];
+    let bytes = write_lockfile(&lock).unwrap();
+
+    // Tag, the hashes array (begin, end, two u64s), then the records array:
+    // begin, end and 24-byte records with `patchfile_hash_is_null` at byte 15.
+    let tag = bytes.windows(8).position(|w| w == b"pAtChEdD").unwrap();
+    let records = tag + 8 + 16 + 16 + 16;
+    assert_eq!(bytes[records + 15], 0, "hashed patch");
+    assert_eq!(&bytes[records + 16..records + 24], &0x1122_3344_5566_7788u64.to_le_bytes());
+    assert_eq!(bytes[records + 24 + 15], 1, "unhashed patch");
+
+    let (reparsed, _) = parse_lockfile_from_bytes(&bytes).unwrap();
+    let hashes: Vec<Option<u64>> = reparsed.trailers.patched.iter().map(|p| p.patch_hash).collect();
+    assert_eq!(hashes, [Some(0x1122_3344_5566_7788), None]);
+}
+
+#[test]
+fn edited_packages_get_a_fresh_meta_hash() {
+    let text = parse_any_lockfile(&example("text-lockfile/bun.lock")).expect("parse");
+    let mut lock = roundtrip(&text);
+    assert_eq!(lock.meta_hash_matches(), Some(true));
+
+    let (id, version) = lock
+        .packages
+        .iter_mut()
+        .enumerate()
+        .find_map(|(id, p)| match &mut p.resolution {
+            ResolutionKind::Npm { version, .. } => Some((id, version)),
+            _ => None,
+        })
+        .expect("text example has an npm package");
+    version.push_str("-edited");
+    let edited = roundtrip(&lock);
+    assert!(edited.packages[id].version.ends_with("-edited"));
+    assert_eq!(edited.meta_hash_matches(), Some(true));
+    assert_ne!(edited.meta_hash, lock.meta_hash);
+}
@@ -1,15 +1,17 @@
 use anyhow::{Context, Result};
 use binrw::Error as BinrwError;
+use bun_xray_core::model::{BehaviorFlags, OrphanKind, PackageColumn};
 use bun_xray_core::{
-    load_package_json, parse_lockfile_with_warnings, PackageJson, ParseError, ScanResult,
-    SecurityScanner,
+    explain_lockfile, load_package_json, parse_lockfile_from_bytes_with_options, LockfileFormat,
+    PackageJson, ParseError, ParseOptions, ParseWarning, Region, ScanResult, SecurityScanner,
 };
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::*;
 use comfy_table::{presets::UTF8_FULL, Cell, ContentArrangement, Table};
 use serde::Serialize;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Audit Bun bun.lockb for supply chain risks", long_about = None)]
@@ -20,32 +22,66 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Audit a bun.lockb file
-    Audit {
-        /// Path to bun.lockb
-        path: PathBuf,
-        /// Output JSON only
-        #[arg(long)]
-        json: bool,
-        /// Verbose parser diagnostics
-        #[arg(long)]
-        verbose: bool,
-        /// Minimum severity that triggers non-zero exit (info|warn|high)
-        #[arg(long, default_value = "warn")]
-        severity_threshold: String,
-        /// Allow registries (host substring). If set, only these are considered trusted.
-        #[arg(long = "allow-registry")]
-        allow_registry: Vec<String>,
-        /// Ignore registries (host substring). Skip warnings for these registries.
-        #[arg(long = "ignore-registry")]
-        ignore_registry: Vec<String>,
-        /// Ignore specific package names (exact match).
-        #[arg(long = "ignore-package")]
-        ignore_package: Vec<String>,
-        /// Optional package.json path (defaults to sibling of lockfile)
-        #[arg(long = "package-json", value_name = "PATH")]
-        package_json: Option<PathBuf>,
-    },
+    /// Audit a bun.lockb (binary) or bun.lock (text) lockfile
+    Audit(AuditArgs),
+    /// Salvage what still decodes from a damaged or truncated bun.lockb
+    Recover(RecoverArgs),
+    /// Map every byte range of a bun.lockb to the structure stored there
+    Explain(ExplainArgs),
+}
+
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// Path to bun.lockb or bun.lock (format is detected automatically); `-` reads stdin
+    path: PathBuf,
+    /// Output JSON only
+    #[arg(long)]
+    json: bool,
+    /// Verbose parser diagnostics
+    #[arg(long)]
+    verbose: bool,
+    /// Minimum severity that triggers non-zero exit (info|warn|high)
+    #[arg(long, default_value = "warn")]
+    severity_threshold: String,
+    /// Allow registries (host substring). If set, only these are considered trusted.
+    #[arg(long = "allow-registry")]
+    allow_registry: Vec<String>,
+    /// Ignore registries (host substring). Skip warnings for these registries.
+    #[arg(long = "ignore-registry")]
+    ignore_registry: Vec<String>,
+    /// Ignore specific package names (exact match).
+    #[arg(long = "ignore-package")]
+    ignore_package: Vec<String>,
+    /// Optional package.json path (defaults to sibling of lockfile)
+    #[arg(long = "package-json", value_name = "PATH")]
+    package_json: Option<PathBuf>,
+    /// Refuse to report unless the lockfile decodes without any parser warning and shows no sign of tampering
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Args, Debug)]
+struct RecoverArgs {
+    /// Path to the damaged lockfile; `-` reads stdin
+    path: PathBuf,
+    /// Output JSON only
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct ExplainArgs {
+    /// Path to bun.lockb; `-` reads stdin
+    path: PathBuf,
+    /// Output JSON only
+    #[arg(long)]
+    json: bool,
+    /// Only show ranges belonging to this package (exact name)
+    #[arg(long)]
+    package: Option<String>,
+    /// Dump the raw bytes under each range
+    #[arg(long)]
+    hexdump: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -89,6 +125,22 @@ struct Issue {
     package: String,
     version: String,
     detail: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    workspaces: Vec<String>,
+    /// Set for parser warnings.
+    #[serde(flatten)]
+    location: Option<WarningLocation>,
+}
+
+/// Where a parser warning points in the lockfile.
+#[derive(Debug, Clone, Serialize)]
+struct WarningLocation {
+    code: &'static str,
+    structure: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package_index: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -100,6 +152,16 @@ struct Summary {
     info_count: usize,
     exit_code: i32,
     parser_warnings: Vec<String>,
+    format: FormatSummary,
+}
+
+/// Which lockfile layout was decoded.
+#[derive(Debug, Serialize)]
+struct FormatSummary {
+    source: LockfileFormat,
+    version: u32,
+    /// Binary package table columns; empty for text lockfiles.
+    columns: Vec<PackageColumn>,
 }
 
 #[derive(Serialize)]
@@ -108,48 +170,78 @@ struct JsonReport<'a> {
     issues: &'a [Issue],
     #[serde(skip_serializing_if = "Option::is_none")]
     trailers: Option<&'a bun_xray_core::model::TrailerInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    install_tree: Option<&'a bun_xray_core::model::InstallTree>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packages: Option<&'a [bun_xray_core::Package]>,
+}
+
+/// A salvaged package and the fields that could not be decoded.
+#[derive(Debug, Serialize)]
+struct RecoveredPackage {
+    index: u32,
+    name: String,
+    version: String,
+    /// Paths relative to the package, e.g. `resolution.npm.version.pre`.
+    damaged: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RecoveryReport<'a> {
+    packages: &'a [RecoveredPackage],
+    damage: &'a [ParseWarning],
+}
+
+#[derive(Serialize)]
+struct ExplainReport<'a> {
+    regions: &'a [&'a Region],
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
-        Commands::Audit {
-            path,
-            json,
-            verbose,
-            severity_threshold,
-            allow_registry,
-            ignore_registry,
-            ignore_package,
-            package_json,
-        } => run_audit(
-            path,
-            json,
-            verbose,
-            &severity_threshold,
-            allow_registry,
-            ignore_registry,
-            ignore_package,
-            package_json,
-        )?,
+        Commands::Audit(args) => run_audit(args)?,
+        Commands::Recover(args) => run_recover(args)?,
+        Commands::Explain(args) => run_explain(args)?,
     }
     Ok(())
 }
 
-fn run_audit(
-    path: PathBuf,
-    json: bool,
-    verbose: bool,
-    severity_threshold: &str,
-    allow_registry: Vec<String>,
-    ignore_registry: Vec<String>,
-    ignore_package: Vec<String>,
-    package_json: Option<PathBuf>,
-) -> Result<()> {
-    let (lockfile, parser_warnings) =
-        parse_lockfile_with_warnings(path.as_path()).map_err(map_binrw_error)?;
+fn read_lockfile_bytes(path: &Path) -> Result<Vec<u8>> {
+    if path.as_os_str() == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .context("failed to read lockfile from stdin")?;
+        return Ok(bytes);
+    }
+    std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+fn run_audit(args: AuditArgs) -> Result<()> {
+    let AuditArgs {
+        path,
+        json,
+        verbose,
+        severity_threshold,
+        allow_registry,
+        ignore_registry,
+        ignore_package,
+        package_json,
+        strict,
+    } = args;
+    let from_stdin = path.as_os_str() == "-";
+    let bytes = read_lockfile_bytes(&path)?;
+    let options = ParseOptions {
+        strict,
+        ..ParseOptions::default()
+    };
+    let (lockfile, parser_warnings) = parse_lockfile_from_bytes_with_options(&bytes, options)
+        .map_err(|err| describe_parse_error(err, &bytes))?;
 
-    let package_json = resolve_package_json(&path, package_json)?;
+    // There is no directory to look for package.json next to stdin.
+    let lockfile_path = (!from_stdin).then_some(path.as_path());
+    let package_json = resolve_package_json(lockfile_path, package_json)?;
     let scan = lockfile.scan(package_json.as_ref());
 
     let issues = collect_issues(
@@ -161,7 +253,7 @@ fn run_audit(
         &ignore_package,
     );
 
-    let sev_threshold = Severity::from_str(severity_threshold).unwrap_or(Severity::Warn);
+    let sev_threshold = Severity::from_str(&severity_threshold).unwrap_or(Severity::Warn);
     let exit_code = decide_exit_code(&issues, sev_threshold);
 
     let summary = Summary {
@@ -176,6 +268,11 @@ fn run_audit(
             .filter(|i| i.kind == "parser_warning")
             .map(|i| i.detail.clone())
             .collect(),
+        format: FormatSummary {
+            source: lockfile.source_format,
+            version: lockfile.format_version,
+            columns: lockfile.columns.clone(),
+        },
     };
 
     if json {
@@ -183,6 +280,8 @@ fn run_audit(
             summary: &summary,
             issues: &issues,
             trailers: if verbose { Some(&lockfile.trailers) } else { None },
+            install_tree: if verbose { Some(&lockfile.install_tree) } else { None },
+            packages: if verbose { Some(&lockfile.packages) } else { None },
         };
         let output = serde_json::to_string_pretty(&report)?;
         println!("{}", output);
@@ -192,12 +291,19 @@ fn run_audit(
                 eprintln!("[warn] {}", w);
             }
             eprintln!(
-                "[info] trailers: trusted={} overrides={} patched={} catalogs={} workspaces={}",
+                "[info] format: {:?} v{} ({} package columns)",
+                lockfile.source_format,
+                lockfile.format_version,
+                lockfile.columns.len()
+            );
+            eprintln!(
+                "[info] trailers: trusted={} overrides={} patched={} catalogs={} workspaces={} unknown={}",
                 lockfile.trailers.trusted_hashes.len(),
                 lockfile.trailers.overrides.len(),
                 lockfile.trailers.patched.len(),
                 lockfile.trailers.catalogs.len(),
-                lockfile.trailers.workspaces_count
+                lockfile.trailers.workspaces.len(),
+                lockfile.trailers.unknown.len()
             );
         }
         render_summary(&summary);
@@ -210,7 +316,7 @@ fn run_audit(
 fn collect_issues(
     scan: &ScanResult,
     lockfile: &bun_xray_core::Lockfile,
-    parser_warnings: Vec<String>,
+    parser_warnings: Vec<ParseWarning>,
     allow_registry: &[String],
     ignore_registry: &[String],
     ignore_package: &[String],
@@ -230,6 +336,8 @@ fn collect_issues(
             package: pkg.name.clone(),
             version: pkg.version.clone(),
             detail,
+            workspaces: Vec::new(),
+            location: None,
         });
         id += 1;
     };
@@ -242,6 +350,128 @@ fn collect_issues(
             pkg.integrity_hash.clone().unwrap_or_default(),
         );
     }
+    for shadow in &scan.hoisting_shadows {
+        let requester = if shadow.dependent_path.is_empty() {
+            "project root".to_string()
+        } else {
+            shadow.dependent_path.clone()
+        };
+        push_issue(
+            Severity::High,
+            "hoisting_shadow",
+            &shadow.loaded,
+            format!(
+                "{} resolves to {}@{} but {} is loaded first from {}",
+                requester,
+                shadow.expected.name,
+                shadow.expected.version,
+                shadow.loaded.version,
+                shadow.loaded_path
+            ),
+        );
+    }
+    for mismatch in &scan.dependency_name_mismatches {
+        let edge = if mismatch.expected_name == mismatch.dependency {
+            format!("dependency {}", mismatch.dependency)
+        } else {
+            format!("dependency {} ({})", mismatch.dependency, mismatch.req)
+        };
+        push_issue(
+            Severity::High,
+            "dependency_name_mismatch",
+            &mismatch.resolved,
+            format!(
+                "{}@{} {} should resolve to {} but points at packages[{}] named {}",
+                mismatch.dependent.name,
+                mismatch.dependent.version,
+                edge,
+                mismatch.expected_name,
+                mismatch.resolved_package_id,
+                mismatch.resolved.name
+            ),
+        );
+    }
+    for violation in &scan.range_violations {
+        // Bun installs past an unmet peer range with a warning; anything else
+        // outside its range was not resolved by Bun.
+        let severity = if violation.behavior.contains(BehaviorFlags::PEER) {
+            Severity::Warn
+        } else {
+            Severity::High
+        };
+        push_issue(
+            severity,
+            "range_violation",
+            &violation.resolved,
+            format!(
+                "{}@{} requests {} {} ({}) but it resolves to {}",
+                violation.dependent.name,
+                violation.dependent.version,
+                violation.dependency,
+                violation.req,
+                violation.range,
+                violation.resolved.version
+            ),
+        );
+    }
+    for finding in &scan.lifecycle_scripts {
+        if finding.indicators.is_empty() {
+            push_issue(
+                Severity::Info,
+                "lifecycle_script",
+                &finding.package,
+                format!("{}: {}", finding.hook, finding.command),
+            );
+        } else {
+            let indicators: Vec<&str> = finding.indicators.iter().map(|i| i.as_str()).collect();
+            push_issue(
+                Severity::High,
+                "suspicious_script",
+                &finding.package,
+                format!("{}: {} [{}]", finding.hook, finding.command, indicators.join(", ")),
+            );
+        }
+    }
+    for conflict in &scan.bin_conflicts {
+        let owners: Vec<&str> = conflict.packages.iter().map(|p| p.name.as_str()).collect();
+        for pkg in &conflict.packages {
+            push_issue(
+                Severity::Warn,
+                "bin_conflict",
+                pkg,
+                format!("`{}` also provided by {}", conflict.executable, owners.join(", ")),
+            );
+        }
+    }
+    for shadow in &scan.bin_shadowing {
+        push_issue(
+            Severity::High,
+            "bin_shadowing",
+            &shadow.package,
+            format!("Transitive dependency exposes well-known binary `{}`", shadow.executable),
+        );
+    }
+    for undeclared in &scan.undeclared_workspaces {
+        push_issue(Severity::High, "undeclared_workspace", &undeclared.package, undeclared.to_string());
+    }
+    for mismatch in &lockfile.name_hash_mismatches {
+        let Some(pkg) = lockfile.packages.get(mismatch.package_id as usize) else {
+            continue;
+        };
+        let subject = match mismatch.dependency_index {
+            Some(_) => format!("dependency `{}`", mismatch.name),
+            None => "package name".to_string(),
+        };
+        push_issue(
+            Severity::High,
+            "name_hash_mismatch",
+            pkg,
+            format!(
+                "Stored hash {:#018x} for {} does not match computed {:#018x}",
+                mismatch.stored, subject, mismatch.computed
+            ),
+        );
+    }
     for pkg in &scan.phantom_dependencies {
         push_issue(Severity::Warn, "phantom_dependency", pkg, "Not declared in package.json".into());
     }
@@ -268,26 +498,235 @@ fn collect_issues(
                 package: pkg.name.clone(),
                 version: pkg.version.clone(),
                 detail: "No integrity hash".into(),
+                workspaces: Vec::new(),
+                location: None,
             });
             id += 1;
         }
     }
 
-    for w in parser_warnings {
+    for pkg in &lockfile.packages {
+        // Packages with decoded scripts are already reported per hook above.
+        if pkg.meta.has_install_script == Some(true)
+            && pkg.scripts.is_empty()
+            && !ignore_pkg.contains(&pkg.name)
+        {
+            issues.push(Issue {
+                id,
+                severity: Severity::Info,
+                kind: "install_script".into(),
+                package: pkg.name.clone(),
+                version: pkg.version.clone(),
+                detail: format!(
+                    "Runs install scripts (origin={:?}, os={}, arch={})",
+                    pkg.meta.origin,
+                    pkg.meta.os.label(),
+                    pkg.meta.arch.label()
+                ),
+                workspaces: Vec::new(),
+                location: None,
+            });
+            id += 1;
+        }
+    }
+
+    if lockfile.meta_hash_matches() == Some(false) {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        issues.push(Issue {
+            id,
+            severity: Severity::High,
+            kind: "meta_hash_mismatch".into(),
+            package: "-".into(),
+            version: "-".into(),
+            detail: format!(
+                "Header meta hash {} does not match recomputed {}",
+                hex(&lockfile.meta_hash),
+                hex(&lockfile.computed_meta_hash.unwrap_or_default())
+            ),
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for unknown in &lockfile.trailers.unknown {
         issues.push(Issue {
             id,
             severity: Severity::Warn,
-            kind: "parser_warning".into(),
+            kind: "unknown_trailer".into(),
             package: "-".into(),
             version: "-".into(),
-            detail: w,
+            detail: format!(
+                "{} bytes at offset {} are unaccounted for (unknown trailer tag {})",
+                unknown.raw_len, unknown.offset, unknown.tag
+            ),
+            workspaces: Vec::new(),
+            location: None,
         });
         id += 1;
     }
 
+    // Bun zero-fills alignment gaps; anything else between structures was
+    // put there by something other than Bun.
+    for slack in lockfile.coverage.slack.iter().filter(|s| !s.is_alignment_padding()) {
+        let (severity, what) = match slack.zeroed {
+            true => (Severity::Info, "zero bytes"),
+            false => (Severity::High, "bytes"),
+        };
+        issues.push(Issue {
+            id,
+            severity,
+            kind: "slack_space".into(),
+            package: "-".into(),
+            version: "-".into(),
+            detail: format!(
+                "{} {} at offset {:#x} belong to no structure (entropy {:.2} bits/byte)",
+                slack.len, what, slack.offset, slack.entropy
+            ),
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for orphan in &lockfile.coverage.orphan_strings {
+        let severity = match orphan.kind {
+            OrphanKind::Text => Severity::Warn,
+            OrphanKind::Url | OrphanKind::Shell | OrphanKind::Base64 => Severity::High,
+        };
+        issues.push(Issue {
+            id,
+            severity,
+            kind: "orphan_string".into(),
+            package: "-".into(),
+            version: "-".into(),
+            detail: format!(
+                "Unreferenced {} string in string_bytes at offset {:#x}: {:?}",
+                orphan.kind.label(),
+                orphan.offset,
+                truncate(&orphan.value, EXPLAIN_VALUE_WIDTH)
+            ),
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for (first, second) in &lockfile.coverage.overlaps {
+        issues.push(Issue {
+            id,
+            severity: Severity::High,
+            kind: "overlapping_structures".into(),
+            package: "-".into(),
+            version: "-".into(),
+            detail: format!(
+                "{} ({:#x}..{:#x}) overlaps {} ({:#x}..{:#x})",
+                second.structure, second.start, second.end, first.structure, first.start, first.end
+            ),
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for finding in &lockfile.non_canonical {
+        let pkg = finding.package_id.and_then(|i| lockfile.packages.get(i as usize));
+        if pkg.is_some_and(|p| ignore_pkg.contains(&p.name)) {
+            continue;
+        }
+        issues.push(Issue {
+            id,
+            severity: Severity::High,
+            kind: "non_canonical_encoding".into(),
+            package: pkg.map_or_else(|| "-".into(), |p| p.name.clone()),
+            version: pkg.map_or_else(|| "-".into(), |p| p.version.clone()),
+            detail: format!(
+                "{} in {} ({} bytes at offset {:#x}); Bun writes zeros here",
+                finding.kind.label(),
+                finding.field,
+                finding.len,
+                finding.offset
+            ),
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for unresolved in &scan.unresolved_trailer_hashes {
+        // A trusted or patched entry naming nothing is more telling than a
+        // stale override for a transitive dependency that was since dropped.
+        let severity = match unresolved.trailer {
+            "override" => Severity::Info,
+            _ => Severity::Warn,
+        };
+        let mut detail = format!(
+            "{} trailer hash {:#018x} matches no package",
+            unresolved.trailer, unresolved.hash
+        );
+        if let Some(extra) = &unresolved.detail {
+            detail.push_str(&format!(" ({})", extra));
+        }
+        issues.push(Issue {
+            id,
+            severity,
+            kind: "unresolved_trailer_hash".into(),
+            package: "-".into(),
+            version: "-".into(),
+            detail,
+            workspaces: Vec::new(),
+            location: None,
+        });
+        id += 1;
+    }
+
+    for w in parser_warnings {
+        let package = w.package();
+        issues.push(Issue {
+            id,
+            severity: Severity::Warn,
+            kind: "parser_warning".into(),
+            package: package.map_or_else(|| "-".into(), |p| p.name.clone()),
+            version: package
+                .and_then(|p| lockfile.packages.get(p.index as usize))
+                .map_or_else(|| "-".into(), |p| p.version.clone()),
+            detail: w.to_string(),
+            workspaces: Vec::new(),
+            location: Some(WarningLocation {
+                code: w.code(),
+                structure: w.structure(),
+                offset: w.offset(),
+                package_index: package.map(|p| p.index),
+            }),
+        });
+        id += 1;
+    }
+
+    attribute_workspaces(&mut issues, lockfile);
     issues
 }
 
+/// Tags each package-level issue with the workspaces that pull it in.
+fn attribute_workspaces(issues: &mut [Issue], lockfile: &bun_xray_core::Lockfile) {
+    let membership = lockfile.workspace_membership();
+    let mut by_package: HashMap<(&str, &str), Vec<String>> = HashMap::new();
+    for (pkg, members) in lockfile.packages.iter().zip(&membership) {
+        let entry = by_package
+            .entry((pkg.name.as_str(), pkg.version.as_str()))
+            .or_default();
+        for ws in members {
+            if !entry.contains(ws) {
+                entry.push(ws.clone());
+            }
+        }
+    }
+    for issue in issues.iter_mut() {
+        if let Some(ws) = by_package.get(&(issue.package.as_str(), issue.version.as_str())) {
+            issue.workspaces = ws.clone();
+        }
+    }
+}
+
 fn registry_allowed(registry: &str, allow: &[String], ignore: &[String]) -> bool {
     let host = extract_host(registry).unwrap_or(registry).to_ascii_lowercase();
     if ignore.iter().any(|r| host.contains(&r.to_ascii_lowercase())) {
@@ -305,9 +744,7 @@ fn decide_exit_code(issues: &[Issue], threshold: Severity) -> i32 {
     let info = issues.iter().any(|i| i.severity == Severity::Info);
     if high && Severity::High >= threshold {
         2
-    } else if warn && Severity::Warn >= threshold {
-        1
-    } else if info && Severity::Info >= threshold {
+    } else if (warn && Severity::Warn >= threshold) || (info && Severity::Info >= threshold) {
         1
     } else {
         0
@@ -315,14 +752,14 @@ fn decide_exit_code(issues: &[Issue], threshold: Severity) -> i32 {
 }
 
 fn resolve_package_json(
-    lockfile_path: &PathBuf,
+    lockfile_path: Option<&Path>,
     explicit: Option<PathBuf>,
 ) -> Result<Option<PackageJson>> {
     let candidate = if let Some(path) = explicit {
         Some(path)
     } else {
         lockfile_path
-            .parent()
+            .and_then(Path::parent)
             .map(|p| p.join("package.json"))
             .filter(|p| p.exists())
     };
@@ -337,6 +774,154 @@ fn resolve_package_json(
     }
 }
 
+/// Exits 1 when anything was damaged, so scripts can tell a clean salvage.
+fn run_recover(args: RecoverArgs) -> Result<()> {
+    let bytes = read_lockfile_bytes(&args.path)?;
+    let options = ParseOptions {
+        recover: true,
+        ..ParseOptions::default()
+    };
+    let (lockfile, damage) = parse_lockfile_from_bytes_with_options(&bytes, options)
+        .map_err(|err| describe_parse_error(err, &bytes))?;
+
+    let mut fields = damaged_fields(&damage, lockfile.packages.len());
+    let packages: Vec<RecoveredPackage> = lockfile
+        .packages
+        .iter()
+        .enumerate()
+        .map(|(idx, pkg)| RecoveredPackage {
+            index: idx as u32,
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            damaged: std::mem::take(&mut fields[idx]),
+        })
+        .collect();
+
+    if args.json {
+        let report = RecoveryReport {
+            packages: &packages,
+            damage: &damage,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        render_recovery(&packages, &damage);
+    }
+    if !damage.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Attributes damage to packages: which of each package's fields are
+/// missing or were decoded as empty.
+fn damaged_fields(damage: &[ParseWarning], package_count: usize) -> Vec<Vec<String>> {
+    let mut fields = vec![Vec::new(); package_count];
+    for warning in damage {
+        let field = match warning {
+            ParseWarning::ColumnTruncated { decoded, .. } => {
+                let column = warning.structure().trim_start_matches("packages.");
+                for package in fields.iter_mut().skip(*decoded) {
+                    package.push(column.to_string());
+                }
+                continue;
+            }
+            ParseWarning::Damaged {
+                path,
+                package: Some(package),
+                ..
+            } => {
+                let prefix = format!("packages[{}].", package.index);
+                path.strip_prefix(&prefix).unwrap_or(path).to_string()
+            }
+            other => other.structure().trim_start_matches("packages.").to_string(),
+        };
+        if let Some(package) = warning.package() {
+            if let Some(slot) = fields.get_mut(package.index as usize) {
+                slot.push(field);
+            }
+        }
+    }
+    fields
+}
+
+fn render_recovery(packages: &[RecoveredPackage], damage: &[ParseWarning]) {
+    let damaged: Vec<&RecoveredPackage> =
+        packages.iter().filter(|p| !p.damaged.is_empty()).collect();
+    println!(
+        "{} {} packages recovered ({} intact, {} damaged)",
+        "✅".green(),
+        packages.len(),
+        packages.len() - damaged.len(),
+        damaged.len()
+    );
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Index").fg(comfy_table::Color::Blue),
+            Cell::new("Package").fg(comfy_table::Color::Blue),
+            Cell::new("Version").fg(comfy_table::Color::Blue),
+            Cell::new("Damaged fields").fg(comfy_table::Color::Blue),
+        ]);
+    for pkg in &damaged {
+        table.add_row(vec![
+            Cell::new(pkg.index),
+            Cell::new(if pkg.name.is_empty() { "?" } else { pkg.name.as_str() }),
+            Cell::new(pkg.version.as_str()),
+            Cell::new(pkg.damaged.join(", ")).fg(comfy_table::Color::Yellow),
+        ]);
+    }
+    if !damaged.is_empty() {
+        println!("\n{}", table);
+    }
+
+    // Damage not tied to one package: truncated columns, buffers, trailers.
+    for warning in damage.iter().filter(|w| w.package().is_none()) {
+        println!("{} {}", "[damage]".yellow(), warning);
+    }
+}
+
+fn run_explain(args: ExplainArgs) -> Result<()> {
+    let bytes = read_lockfile_bytes(&args.path)?;
+    let regions = explain_lockfile(&bytes).map_err(|err| describe_parse_error(err, &bytes))?;
+    let shown: Vec<&Region> = regions
+        .iter()
+        .filter(|r| match &args.package {
+            Some(name) => r.package.as_ref().is_some_and(|p| &p.name == name),
+            None => true,
+        })
+        .collect();
+    if let (Some(name), true) = (&args.package, shown.is_empty()) {
+        anyhow::bail!("no package named {:?} in {}", name, args.path.display());
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&ExplainReport { regions: &shown })?);
+        return Ok(());
+    }
+    for region in shown {
+        let value = truncate(&region.value.replace('\n', "\\n"), EXPLAIN_VALUE_WIDTH);
+        println!(
+            "{:08x}..{:08x} {:>6}  {:<40} {}",
+            region.start,
+            region.end,
+            region.len(),
+            region.path.cyan(),
+            value
+        );
+        for note in &region.notes {
+            println!("{:26}{} {}", "", "↳".yellow(), note);
+        }
+        if args.hexdump {
+            let end = (region.end as usize).min(bytes.len());
+            print!("{}", hexdump_range(&bytes, region.start as usize, end));
+        }
+    }
+    Ok(())
+}
+
 fn render_summary(sum: &Summary) {
     println!("{} {} packages parsed", "✅".green(), sum.total_packages);
     if sum.high_count == 0 && sum.warn_count == 0 && sum.info_count == 0 {
@@ -388,6 +973,73 @@ fn extract_host(url: &str) -> Option<&str> {
     url.split('/').next()
 }
 
+/// Rows of hexdump shown on each side of the one holding a failing byte.
+const HEXDUMP_CONTEXT_ROWS: usize = 2;
+/// Longer decoded values are cut in `explain` text output and issue details.
+const EXPLAIN_VALUE_WIDTH: usize = 72;
+
+/// Reports a parse failure with its structure path, offset and the bytes
+/// around it, when the parser could locate it.
+fn describe_parse_error(err: ParseError, bytes: &[u8]) -> anyhow::Error {
+    let mut report = match err.offset() {
+        Some(offset) => format!("{}\n{}", err, hexdump(bytes, offset as usize)),
+        None if matches!(err.kind(), ParseError::Anomalies(_)) => format!("{}\n", err),
+        None => return map_binrw_error(err),
+    };
+    // Strict mode: list every anomaly, not just the first.
+    if let ParseError::Anomalies(warnings) = err.kind() {
+        for w in warnings {
+            report.push_str(&format!("  - [{}] {}\n", w.code(), w));
+        }
+    }
+    anyhow::anyhow!(report.trim_end().to_string())
+}
+
+/// Classic 16-byte-per-row dump around `offset`, with a caret under it.
+fn hexdump(bytes: &[u8], offset: usize) -> String {
+    let row = offset / 16;
+    let first = row.saturating_sub(HEXDUMP_CONTEXT_ROWS);
+    let last = (row + HEXDUMP_CONTEXT_ROWS).min(bytes.len().saturating_sub(1) / 16);
+    let mut out = String::new();
+    for r in first..=last {
+        let start = r * 16;
+        let chunk = &bytes[start.min(bytes.len())..(start + 16).min(bytes.len())];
+        out.push_str(&hexdump_row(chunk, start));
+        if r == row {
+            out.push_str(&format!("  {:8}  {}^^\n", "", " ".repeat((offset - start) * 3)));
+        }
+    }
+    if offset >= bytes.len() {
+        out.push_str(&format!("  (offset is past the end of the {}-byte input)\n", bytes.len()));
+    }
+    out
+}
+
+/// Cuts `text` to `width` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    text.chars().take(width - 1).collect::<String>() + "…"
+}
+
+/// [`hexdump`] rows covering exactly `start..end`, without context.
+fn hexdump_range(bytes: &[u8], start: usize, end: usize) -> String {
+    (start..end)
+        .step_by(16)
+        .map(|row| hexdump_row(&bytes[row..(row + 16).min(end)], row))
+        .collect()
+}
+
+fn hexdump_row(chunk: &[u8], start: usize) -> String {
+    let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+    let ascii: String = chunk
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect();
+    format!("  {:08x}  {:<47}  |{}|\n", start, hex.join(" "), ascii)
+}
+
 fn map_binrw_error(err: ParseError) -> anyhow::Error {
     match err {
         ParseError::Binrw(BinrwError::Io(e)) => e.into(),